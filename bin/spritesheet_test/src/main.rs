@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::OnceLock};
 
 use macroquad::{
     prelude::*,
@@ -6,13 +6,24 @@ use macroquad::{
 };
 use noise::{Fbm, Perlin};
 use rust_nonamerl_core::{
+    activator::UseMode,
+    component::{Blocking, Energy, Faction, Gold, Health, Perception, Position, Xp, ACTION_COST},
+    compute_fov,
+    equipment::Equipment,
+    event::WorldEvent,
+    inventory::Inventory,
     item::{ItemBuilder, ItemKind},
-    property::{HealthData, Property},
+    level::Level,
+    property::Property,
+    status::{StatusEffect, StatusEffects, StatusKind},
+    template::{EntityTemplates, ItemTemplates},
     world::{EntityKey, ItemKey, World},
-    Action, ActionQueue, AddSpriteOptions, BuilderAlgoWithNoise, Camera, Camera2D, Dimension2,
-    Dimension2D, FovOccluder, IntExtent2D, IntVector2, ItemContainer, Map, MapBuilder, MapCommand,
-    MapCommands, MoveAction, RandomWalkBuilder, RenderOp, Renderer, RoomBuilder, SpriteSheet, Tile,
-    TileSpriteInfo, Vec2, Viewport, VisibilityOcclusion, Visible, Visited, Walkable,
+    Action, ActionQueue, BuilderAlgoWithNoise, Camera, Camera2D, Dimension2, Dimension2D,
+    Environment, EquipAction, Flammable, FovOccluder, FromTileDefinition, IntExtent2D, IntVector2,
+    ItemContainer, Map, MapBuilder, MapCommand, MapCommands, MoveAction, PickUpAction,
+    RandomWalkBuilder, RemoveIsolatedTiles, RenderOp, Renderer, RoomBuilder, SpriteSheet, Tile,
+    TileDefinition, TileId, TileRegistry, TileSpriteInfo, TileTransition, Trigger, TriggerEffect,
+    TriggerEvent, UseItemAction, Vec2, Viewport, VisibilityOcclusion, Visible, Visited, Walkable,
 };
 
 fn window_conf() -> Conf {
@@ -25,49 +36,64 @@ fn window_conf() -> Conf {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum TileKind {
-    Grass,
-    Floor,
-    Wall,
+/// The tile definitions of the demo, loaded once so tiles can follow their transitions.
+static TILE_REGISTRY: OnceLock<TileRegistry> = OnceLock::new();
+
+fn tile_registry() -> &'static TileRegistry {
+    TILE_REGISTRY.get_or_init(|| TileRegistry::load("data/tiles.json").unwrap())
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TestTile {
-    pub kind: TileKind,
+    pub id: TileId,
+    pub sprite: &'static str,
+    pub walkable: bool,
+    pub opacity: f32,
+    pub movement_cost: u32,
+    pub flammable: bool,
+    pub obscured: bool,
     pub visited: bool,
     pub visible: bool,
     pub items: Vec<ItemKey>,
+    pub durability: Option<u32>,
 }
 
-impl TestTile {
-    pub fn new(kind: TileKind) -> Self {
-        Self {
-            kind,
-            visited: false,
-            visible: false,
-            items: Vec::new(),
-        }
+impl Tile for TestTile {
+    fn sprite_info(&self) -> TileSpriteInfo {
+        TileSpriteInfo::SpriteSheet(self.sprite)
+    }
+
+    fn durability(&self) -> Option<u32> {
+        self.durability
     }
-}
 
-impl Default for TestTile {
-    fn default() -> Self {
+    fn set_durability(&mut self, durability: u32) {
+        self.durability = Some(durability);
+    }
+
+    fn transition(&self, transition: TileTransition) -> Option<Self> {
+        let registry = tile_registry();
+        let id = registry.transition(self.id, transition)?;
+        Some(TestTile::from_definition(id, registry.get(id)?))
+    }
+}
+impl FromTileDefinition for TestTile {
+    fn from_definition(id: TileId, definition: &TileDefinition) -> Self {
         Self {
-            kind: TileKind::Grass,
+            id,
+            // the renderer wants a 'static name, the demo registry lives for the whole run
+            sprite: tile_registry()
+                .get(id)
+                .map_or("", |known| known.sprite.as_str()),
+            walkable: definition.walkable,
+            opacity: definition.opacity,
+            movement_cost: definition.movement_cost,
+            flammable: definition.has_flag("flammable"),
+            obscured: false,
             visited: false,
             visible: false,
             items: Vec::new(),
-        }
-    }
-}
-
-impl Tile for TestTile {
-    fn sprite_info(&self) -> TileSpriteInfo {
-        match self.kind {
-            TileKind::Grass => TileSpriteInfo::SpriteSheet("grass"),
-            TileKind::Floor => TileSpriteInfo::SpriteSheet("floor"),
-            TileKind::Wall => TileSpriteInfo::SpriteSheet("wall"),
+            durability: definition.durability,
         }
     }
 }
@@ -89,10 +115,31 @@ impl Visited for TestTile {
         self.visited = visited;
     }
 }
-impl FovOccluder for TestTile {}
+impl FovOccluder for TestTile {
+    fn block_visibility(&self) -> VisibilityOcclusion {
+        VisibilityOcclusion::new(1. - self.opacity).unwrap_or(Self::BLOCKED)
+    }
+
+    fn is_obscured(&self) -> bool {
+        self.obscured
+    }
+
+    fn set_obscured(&mut self, obscured: bool) {
+        self.obscured = obscured;
+    }
+}
+impl Flammable for TestTile {
+    fn flammability(&self) -> f32 {
+        if self.flammable {
+            0.3
+        } else {
+            0.
+        }
+    }
+}
 impl Walkable for TestTile {
     fn is_walkable(&self) -> bool {
-        self.kind != TileKind::Wall
+        self.walkable
     }
 }
 
@@ -111,14 +158,17 @@ impl ItemContainer for TestTile {
 }
 
 fn create_player<T: Tile>(world: &mut World<T>, pos: IntVector2) -> EntityKey {
-    let mut entities = world.entities.borrow_mut();
-
-    // entities.get(player_key).unwrap()
-    entities.add("Player", |player| {
-        player.add_property(Property::Xp(5));
-        player.add_property(Property::Health(HealthData { health: 100 }));
-        player.add_property(Property::Position(pos));
-        player.add_property(Property::Gold(0));
+    world.spawn("Player", |player| {
+        player.insert(Xp(5));
+        player.insert(Health::new(100));
+        player.insert(Position(pos));
+        player.insert(Gold(0));
+        player.insert(Blocking);
+        player.insert(Faction::Player);
+        player.insert(Inventory::new(10, 50));
+        player.insert(Level::new());
+        player.insert(Perception(5));
+        player.insert(Energy(ACTION_COST));
     })
 }
 
@@ -143,35 +193,106 @@ where
     item_key
 }
 
+fn describe_event<T: Tile>(world: &World<T>, event: &WorldEvent) -> String {
+    let entities = world.entities.borrow();
+    let name = |key: Option<EntityKey>| {
+        key.and_then(|key| entities.get(key))
+            .map(|entity| entity.name().to_owned())
+            .unwrap_or_else(|| "something".to_owned())
+    };
+    let item_name = |key: ItemKey| {
+        world
+            .items
+            .borrow()
+            .get(key)
+            .map(|item| item.name.clone())
+            .unwrap_or_default()
+    };
+    match event {
+        WorldEvent::Hit {
+            attacker,
+            target,
+            damage,
+            ..
+        } => format!(
+            "{} hits {} for {}",
+            name(*attacker),
+            name(Some(*target)),
+            damage
+        ),
+        WorldEvent::Hurt { entity, damage } => {
+            format!("{} takes {} damage", name(Some(*entity)), damage)
+        }
+        WorldEvent::Missed { attacker, target } => {
+            format!("{} misses {}", name(*attacker), name(Some(*target)))
+        }
+        WorldEvent::Killed { attacker, target } => {
+            format!("{} kills {}", name(*attacker), name(Some(*target)))
+        }
+        WorldEvent::PickedUp { entity, item } => {
+            format!("{} picks up {}", name(Some(*entity)), item_name(*item))
+        }
+        WorldEvent::Dropped { entity, item } => {
+            format!("{} drops {}", name(Some(*entity)), item_name(*item))
+        }
+        WorldEvent::Equipped { entity, item } => {
+            format!("{} equips {}", name(Some(*entity)), item_name(*item))
+        }
+        WorldEvent::Unequipped { entity, item } => {
+            format!("{} takes off {}", name(Some(*entity)), item_name(*item))
+        }
+        WorldEvent::Healed { entity, amount } => {
+            format!("{} heals {}", name(Some(*entity)), amount)
+        }
+        WorldEvent::Consumed { entity, item } => {
+            format!("{} uses up {}", name(Some(*entity)), item_name(*item))
+        }
+        WorldEvent::StatusApplied { entity, kind } => {
+            format!("{} is now {:?}", name(Some(*entity)), kind)
+        }
+        WorldEvent::StatusExpired { entity, kind } => {
+            format!("{} is no longer {:?}", name(Some(*entity)), kind)
+        }
+        WorldEvent::LevelUp { entity, level } => {
+            format!("{} reaches level {}", name(Some(*entity)), level)
+        }
+        WorldEvent::Died { name, .. } => format!("{} dies", name),
+    }
+}
+
 #[macroquad::main(window_conf)]
 async fn main() {
     let mut fov_cells = HashSet::<IntVector2>::new();
-    let mut current_fov_cells = HashSet::<IntVector2>::new();
+    let mut messages = Vec::<String>::new();
     let mut world = World::new();
     let mut action_queue = ActionQueue::new();
+    let mut environment = Environment::new(0);
     let mut map_builder =
         MapBuilder::<TestTile>::new(IntExtent2D::new(0, 0, 100, 100), Dimension2D::new(24, 24));
     let mut world_x = 240.;
     let mut world_y = 240.;
-    map_builder.add_tile("grass", TestTile::default());
-    map_builder.add_tile("water", TestTile::default());
-    map_builder.add_tile("floor", TestTile::new(TileKind::Floor));
-    map_builder.add_tile("wall", TestTile::new(TileKind::Wall));
+    map_builder.add_tiles_from_registry(tile_registry());
 
     // let mut map_commands = MapCommands::default();
+    let grass = map_builder.tile("grass").unwrap();
     let noise = Fbm::<Perlin>::default();
     let f = |x: i32, y: i32, value: f64| {
         // println!("x: {}, y: {}, value: {}", x, y, value);
         if value > 0.1 {
-            Some(TestTile::default())
+            Some(grass.clone())
         } else {
             None
         }
     };
 
-    map_builder.add_step(&RandomWalkBuilder::new(IntVector2::new(10, 10)));
-    map_builder.add_step(&BuilderAlgoWithNoise::new(noise, f));
-    map_builder.add_step(&RoomBuilder::new());
+    map_builder
+        .add_step(&RandomWalkBuilder::new(IntVector2::new(10, 10)))
+        .unwrap();
+    map_builder
+        .add_step(&BuilderAlgoWithNoise::new(noise, f))
+        .unwrap();
+    map_builder.add_step(&RemoveIsolatedTiles::new()).unwrap();
+    map_builder.add_step(&RoomBuilder::new()).unwrap();
 
     let map_size = map_builder.map.size();
 
@@ -179,6 +300,14 @@ async fn main() {
 
     let start_point = map_builder.rooms[1].center(); //IntVector2::new(5, 5);
     let player = create_player(&mut world, start_point);
+    let item_templates = ItemTemplates::load("data/weapons.json").unwrap();
+    let monster_templates = EntityTemplates::load("data/monsters.json").unwrap();
+    for (i, room) in map_builder.rooms.iter().skip(2).enumerate() {
+        let monster = if i % 2 == 0 { "goblin" } else { "rat" };
+        monster_templates
+            .spawn(monster, &world, room.center(), &item_templates)
+            .unwrap();
+    }
 
     let mut draw_ops: Vec<RenderOp<TestTile>> = Vec::new();
     for i in 0..10 {
@@ -191,70 +320,27 @@ async fn main() {
         }
     }
 
+    // a hidden poison needle next to the start, found by searching with S
+    world.triggers.borrow_mut().add(
+        IntVector2::new(start_point.x() + 1, start_point.y()),
+        Trigger::new(
+            TriggerEvent::Stand,
+            TriggerEffect::Status(StatusEffect::new(StatusKind::Poison, 3, 1)),
+        )
+        .hidden(4),
+    );
+
     let item_key = create_item(&mut world, vec![]);
     map.add_command(MapCommand::AddItem(start_point, item_key));
+    let potion = item_templates.build("healing potion", &world).unwrap();
+    map.add_command(MapCommand::AddItem(start_point, potion));
+    let potion = item_templates.build("potion of speed", &world).unwrap();
+    map.add_command(MapCommand::AddItem(start_point, potion));
 
-    let mut texture = load_texture("assets/urizen_onebit_tileset__v1d0.png")
-        .await
-        .unwrap();
-    texture.set_filter(FilterMode::Nearest);
-    let mut sprites = SpriteSheet::new(texture);
-    sprites.add_sprite(
-        "test",
-        Rect {
-            x: 0.,
-            y: 10.,
-            w: 12.,
-            h: 12.,
-        },
-        Some(AddSpriteOptions {
-            gap: (1, 1),
-            size: (12, 12),
-        }),
-    );
-    sprites.add_sprite(
-        "grass",
-        Rect {
-            x: 0.,
-            y: 9.,
-            w: 12.,
-            h: 12.,
-        },
-        Some(AddSpriteOptions {
-            gap: (1, 1),
-            size: (12, 12),
-        }),
-    );
-    sprites.add_sprite(
-        "floor",
-        Rect {
-            x: 0.,
-            y: 2.,
-            w: 12.,
-            h: 12.,
-        },
-        Some(AddSpriteOptions {
-            gap: (1, 1),
-            size: (12, 12),
-        }),
-    );
-
-    sprites.add_sprite(
-        "wall",
-        Rect {
-            x: 0.,
-            y: 3.,
-            w: 12.,
-            h: 12.,
-        },
-        Some(AddSpriteOptions {
-            gap: (1, 1),
-            size: (12, 12),
-        }),
-    );
+    let sprites = SpriteSheet::load("assets/config.json").await.unwrap();
     clear_background(LIGHTGRAY);
 
-    let (rect, texture) = sprites.get_sprite("test");
+    let (rect, texture) = sprites.get_sprite("test").unwrap();
 
     // Creazione del viewport con posizione (0, 0) e dimensioni (800, 600)
     let viewport = Viewport {
@@ -264,7 +350,7 @@ async fn main() {
         height: 600.0,
     };
 
-    let renderer = Renderer::from_map_cell_size(map.cell_size());
+    let mut renderer = Renderer::from_map_cell_size(map.cell_size());
 
     // Creazione della camera inizializzata dal viewport e con scala di zoom 1.0
     let mut camera = Camera2D::from_viewport(0.0, 0.0, &viewport, 1.0);
@@ -272,17 +358,20 @@ async fn main() {
     camera.center_on_world_point(world_x, world_y, &viewport);
 
     loop {
+        renderer.advance(get_frame_time());
         if map.commands_available() {
             map.process_commands();
         }
-        if is_key_down(KeyCode::Right) {
+        // permadeath: once the player is dead the run is over
+        let alive = !world.is_game_over();
+        if alive && is_key_down(KeyCode::Right) {
             world_x += 1.0 * 12.;
             let move_action = MoveAction::new(IntVector2::new(1, 0), player);
             // move_action.perform(&world);
             action_queue.add(Box::new(move_action));
         }
 
-        if is_key_down(KeyCode::Left) {
+        if alive && is_key_down(KeyCode::Left) {
             world_x -= 1.0 * 12.;
             // camera.center_on_fixed_world_point(world_x, world_y, &viewport)
             let move_action = MoveAction::new(IntVector2::new(-1, 0), player);
@@ -290,7 +379,7 @@ async fn main() {
             action_queue.add(Box::new(move_action));
         }
 
-        if is_key_down(KeyCode::Up) {
+        if alive && is_key_down(KeyCode::Up) {
             world_y -= 1.0 * 12.;
             // camera.center_on_fixed_world_point(world_x, world_y, &viewport)
             let move_action = MoveAction::new(IntVector2::new(0, -1), player);
@@ -298,7 +387,7 @@ async fn main() {
             action_queue.add(Box::new(move_action));
         }
 
-        if is_key_down(KeyCode::Down) {
+        if alive && is_key_down(KeyCode::Down) {
             world_y += 1.0 * 12.;
             let move_action = MoveAction::new(IntVector2::new(0, 1), player);
             // move_action.perform(&world);
@@ -306,7 +395,88 @@ async fn main() {
             action_queue.add(Box::new(move_action));
         }
 
+        if alive && is_key_pressed(KeyCode::G) {
+            action_queue.add(Box::new(PickUpAction::new(player)));
+        }
+
+        if alive && is_key_pressed(KeyCode::E) {
+            let last = world
+                .entities
+                .borrow()
+                .get(player)
+                .and_then(|player| player.get::<Inventory>())
+                .and_then(|inventory| inventory.items().last());
+            if let Some(item) = last {
+                action_queue.add(Box::new(EquipAction::new(item, player)));
+            }
+        }
+
+        if alive && is_key_pressed(KeyCode::Q) {
+            let drinkable = world
+                .entities
+                .borrow()
+                .get(player)
+                .and_then(|player| player.get::<Inventory>())
+                .and_then(|inventory| {
+                    let items = world.items.borrow();
+                    inventory.items().find(|item| {
+                        items
+                            .get(*item)
+                            .is_some_and(|item| item.activator(UseMode::Drink).is_some())
+                    })
+                });
+            if let Some(item) = drinkable {
+                action_queue.add(Box::new(UseItemAction::new(item, player, UseMode::Drink)));
+            }
+        }
+
+        if alive && is_key_pressed(KeyCode::S) {
+            let searcher = world.entities.borrow().get(player).and_then(|player| {
+                player
+                    .get::<Position>()
+                    .zip(player.get::<Perception>())
+                    .map(|(Position(pos), Perception(perception))| (*pos, *perception))
+            });
+            if let Some((pos, perception)) = searcher {
+                let found = world.triggers.borrow_mut().search(pos, 2, perception);
+                messages.push(format!("Player finds {} traps", found.len()));
+            }
+        }
+
+        if alive && is_key_pressed(KeyCode::F) {
+            // sets fire to the cells around the player
+            if let Some(Position(pos)) = world
+                .entities
+                .borrow()
+                .get(player)
+                .and_then(|player| player.get::<Position>())
+            {
+                for cell in map.neighbors(*pos) {
+                    environment.ignite(&map, cell);
+                }
+            }
+        }
+
+        // every player action spends energy, and turns pass until the player can act again:
+        // a hasted player acts twice in some turns, a slowed one waits for several
+        if !action_queue.is_empty() {
+            world.spend_energy(player);
+            while !world.can_act(player) {
+                world.gain_energy();
+                action_queue.add_all(world.tick_statuses());
+                let standing = world
+                    .triggers
+                    .borrow_mut()
+                    .standing_actions(&world.spatial.borrow());
+                action_queue.add_all(standing);
+                map.add_commands(environment.step(&map));
+                action_queue.add_all(environment.damage_actions(&world));
+            }
+        }
         action_queue.process_actions(&mut world, &mut map);
+        for event in world.take_events() {
+            messages.push(describe_event(&world, &event));
+        }
 
         let mouse_pos = mouse_position();
 
@@ -319,12 +489,11 @@ async fn main() {
             // camera.center_on_fixed_world_point(world_x, world_y, &viewport)
         }
 
-        if let Some(Property::Position(pos)) = world
+        if let Some(Position(pos)) = world
             .entities
             .borrow()
             .get(player)
-            .unwrap()
-            .get_property(Property::POSITION)
+            .and_then(|player| player.get::<Position>())
         {
             let player_world_pos = (
                 pos.x() as f32 * map.cell_size().width() as f32,
@@ -379,6 +548,27 @@ async fn main() {
             // for i in min_cell.0..max_cell.0 {
             //     for j in min_cell.1..max_cell.1 {
             // println!("cells: {:?} ", map.len());
+            let fov_size = world
+                .entities
+                .borrow()
+                .get(player)
+                .and_then(|player| player.get::<StatusEffects>())
+                .map_or(4, |effects| effects.fov_radius(4));
+            let mut coords = (
+                (world_mouse_pos.0 / map.cell_size().width() as f32) as i32,
+                (world_mouse_pos.1 / map.cell_size().height() as f32) as i32,
+            );
+            if let Some(Position(pos)) = world
+                .entities
+                .borrow()
+                .get(player)
+                .and_then(|player| player.get::<Position>())
+            {
+                coords = (pos.x(), pos.y());
+            }
+
+            let current_fov_cells =
+                compute_fov(&map, IntVector2::new(coords.0, coords.1), fov_size);
             let mut map_batch = Vec::<RenderOp<TestTile>>::new();
             // for i in visibile_cells.left()..visibile_cells.right() {
             //     for j in visibile_cells.top()..visibile_cells.bottom() {
@@ -391,11 +581,38 @@ async fn main() {
                     //     let coords = coords.unwrap();
                     let (i, j) = (pos.x(), pos.y());
 
-                    if let Some(tile) = map.get(i, j) {
-                        map_batch.push(RenderOp::DrawTile(i, j, tile));
+                    if current_fov_cells.contains(&pos) {
+                        if let Some(tile) = map.get(i, j) {
+                            map_batch.push(RenderOp::DrawTile(i, j, tile.clone()));
+                        }
                     }
                     // map_batch.push(RenderOp::DrawTile(i, j, map.get(i, j).unwrap()));
                 });
+            // fog of war: out of sight, the player only sees what it remembers, dimmed
+            if let Some(memory) = world.memories.borrow().get(&player) {
+                for (pos, remembered) in memory.iter() {
+                    if current_fov_cells.contains(&pos)
+                        || !visibile_cells.contains(pos.x(), pos.y())
+                    {
+                        continue;
+                    }
+                    map_batch.push(RenderOp::DrawTile(
+                        pos.x(),
+                        pos.y(),
+                        remembered.tile.clone(),
+                    ));
+                    map_batch.push(RenderOp::FillCell(
+                        pos.x(),
+                        pos.y(),
+                        Color {
+                            r: 0.,
+                            g: 0.,
+                            b: 0.,
+                            a: 0.6,
+                        },
+                    ));
+                }
+            }
             // }
 
             renderer.batch_render(&camera, &viewport, &sprites, &map_batch);
@@ -412,79 +629,26 @@ async fn main() {
                 },
             ));
 
-            // map.fov_iter(start,fov_size)
-
-            let fov_size = 4;
-            let mut coords = (
-                (world_mouse_pos.0 / map.cell_size().width() as f32) as i32,
-                (world_mouse_pos.1 / map.cell_size().height() as f32) as i32,
-            );
-            if let Some(Property::Position(pos)) = world
-                .entities
-                .borrow()
-                .get(player)
-                .unwrap()
-                .get_property(Property::POSITION)
-            {
-                coords = (pos.x(), pos.y());
+            for p in current_fov_cells.iter() {
+                map_batch.push(RenderOp::FillCell(
+                    p.x(),
+                    p.y(),
+                    Color {
+                        r: 1.,
+                        g: 1.,
+                        b: 1.,
+                        a: 0.5,
+                    },
+                ));
             }
+            map.add_commands(
+                current_fov_cells
+                    .iter()
+                    .map(|p| MapCommand::SetVisited(*p, true))
+                    .collect(),
+            );
+            world.remember(player, &map, current_fov_cells.iter().copied());
 
-            // let candidate_fov_cells = Tree::<(IntVector2, TestTile)>::new();
-
-            // loop over the border of a 5x5 grid centerd in the mouse position
-            for i in -fov_size..=fov_size {
-                for j in -fov_size..=fov_size {
-                    if i == fov_size || i == -fov_size || j == fov_size || j == -fov_size {
-                        let target = (coords.0 + i, coords.1 + j);
-                        let path = map.line(
-                            IntVector2::new(coords.0, coords.1),
-                            IntVector2::new(target.0, target.1),
-                        );
-                        // println!("target: {:?}", target);
-                        'outer: for tile in path.iter().map(|v| (v, map.get(v.x(), v.y())))
-                        //.take_while(|x| x.1.is_some())
-                        {
-                            let (p, tile) = tile;
-                            // print!("p: {:?}, ", p);
-                            if current_fov_cells.contains(p) {
-                                // println!("cell already in fov");
-                                continue;
-                            }
-
-                            let t = map.get(p.x(), p.y());
-                            if let Some(tile) = t {
-                                // let tile = tile.unwrap();
-
-                                if !(tile.block_visibility() == TestTile::BLOCKED) {
-                                    map_batch.push(RenderOp::FillCell(
-                                        p.x(),
-                                        p.y(),
-                                        Color {
-                                            r: 1.,
-                                            g: 1.,
-                                            b: 1.,
-                                            a: 0.5,
-                                        },
-                                    ));
-                                    //   BUG: implement map commands
-                                    // (&mut map).set_visited(p.x(), p.y(), true);
-                                    // map.set_visited(p.x(), p.y(), true);
-                                    map.add_command(MapCommand::SetVisited(*p, true));
-                                    current_fov_cells.insert(*p);
-                                } else {
-                                    // println!("tile blocked");
-                                    break 'outer;
-                                }
-                            } else {
-                                // println!("tile not found");
-                                break 'outer;
-                            }
-                        }
-                    }
-
-                    // map_batch.push(RenderOp::HighlightCell(coords.x(), coords.y()));
-                }
-            }
             // println!("fov_cells: {:?}", current_fov_cells);
             let fov_cells_to_remove = fov_cells.difference(&current_fov_cells);
             let fov_cells_to_add = current_fov_cells.difference(&fov_cells);
@@ -501,17 +665,39 @@ async fn main() {
                     .collect(),
             );
 
-            fov_cells = current_fov_cells.clone();
-            current_fov_cells.clear();
+            fov_cells = current_fov_cells;
+
+            for pos in fov_cells.iter() {
+                if world.triggers.borrow().known_at(*pos).next().is_some() {
+                    map_batch.push(RenderOp::FillCell(
+                        pos.x(),
+                        pos.y(),
+                        Color {
+                            r: 0.6,
+                            g: 0.,
+                            b: 0.8,
+                            a: 0.6,
+                        },
+                    ));
+                }
+            }
 
-            if let Some(Property::Position(pos)) = world
-                .entities
-                .borrow()
-                .get(player)
-                .unwrap()
-                .get_property(Property::POSITION)
-            {
-                println!("player pos: {:?}", pos);
+            for (pos, cell) in environment.iter() {
+                if cell.fire > 0 && fov_cells.contains(&pos) {
+                    map_batch.push(RenderOp::FillCell(
+                        pos.x(),
+                        pos.y(),
+                        Color {
+                            r: 1.,
+                            g: 0.4,
+                            b: 0.,
+                            a: 0.6,
+                        },
+                    ));
+                }
+            }
+
+            for (_, pos) in world.spatial.borrow().in_rect(&visibile_cells) {
                 map_batch.push(RenderOp::FillCell(
                     pos.x(),
                     pos.y(),
@@ -618,11 +804,9 @@ async fn main() {
             world
                 .entities
                 .borrow()
-                .get(player)
-                .unwrap()
-                .get_property(property_name)
-                .unwrap()
-                .clone()
+                .get(e)
+                .and_then(|entity| entity.get_property(property_name))
+                .map(|property| property.clone_box())
         };
 
         let gold = get_prop(player, "gold");
@@ -649,31 +833,83 @@ async fn main() {
             );
 
             ui.separator();
-            if let Some(Property::Position(pos)) = world
+            if let Some(Position(pos)) = world
                 .entities
                 .borrow()
                 .get(player)
-                .unwrap()
-                .get_property(Property::POSITION)
+                .and_then(|player| player.get::<Position>())
             {
                 ui.label(None, &format!("player pos: {:?}", pos));
                 if let Some(tile) = map.get(pos.x(), pos.y()) {
                     ui.label(None, &format!("tile: {:?}", tile));
                 };
             };
+            if let Some(memory) = world.memories.borrow().get(&player) {
+                ui.label(None, &format!("remembered tiles: {}", memory.len()));
+            }
             ui.separator();
             // ui.label(None, format!("Au: {}", gold));
             ui.separator();
-            if let Some(Property::Gold(value)) = world
+            if let Some((level, Xp(xp))) = world
+                .entities
+                .borrow()
+                .get(player)
+                .and_then(|player| player.get::<Level>().zip(player.get::<Xp>()))
+            {
+                ui.label(
+                    None,
+                    &format!(
+                        "level {} ({}/{} xp)",
+                        level.level,
+                        xp,
+                        world.progression.curve.xp_for(level.level + 1)
+                    ),
+                );
+            }
+            if let Some(Gold(value)) = world
                 .entities
                 .borrow()
                 .get(player)
-                .unwrap()
-                .get_property(Property::GOLD)
+                .and_then(|player| player.get::<Gold>())
             {
                 ui.label(None, &format!("gold: {:?}", value));
             };
+            if let Some(inventory) = world
+                .entities
+                .borrow()
+                .get(player)
+                .and_then(|player| player.get::<Inventory>())
+            {
+                ui.label(
+                    None,
+                    &format!(
+                        "inventory: {}/{} slots, weight {}/{}",
+                        inventory.slots_used(),
+                        inventory.capacity,
+                        inventory.weight(&world.items.borrow()),
+                        inventory.max_weight
+                    ),
+                );
+            }
+            if let Some(equipment) = world
+                .entities
+                .borrow()
+                .get(player)
+                .and_then(|player| player.get::<Equipment>())
+            {
+                let bonus = equipment.bonus(&world.items.borrow());
+                ui.label(
+                    None,
+                    &format!("damage: {}, defense: {}", bonus.damage, bonus.defense),
+                );
+            }
             ui.separator();
+            if !alive {
+                ui.label(None, "You died. Game over.");
+            }
+            for message in messages.iter().rev().take(5) {
+                ui.label(None, message);
+            }
         });
 
         next_frame().await
@@ -0,0 +1,259 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use macroquad::{
+    file::load_string,
+    prelude::{FilterMode, Rect},
+    texture::{load_texture, Texture2D},
+};
+use serde::Deserialize;
+
+use crate::{Animation, AnimationFrame, LoopMode, Sprite, SpriteSheet};
+
+#[derive(Debug)]
+pub enum SpriteSheetError {
+    /// The atlas or one of its textures could not be loaded.
+    Load(String),
+    Parse(serde_json::Error),
+    UnknownSprite(String),
+    /// A sprite refers to a texture index not listed in the atlas.
+    UnknownTexture {
+        sprite: String,
+        texture: usize,
+    },
+    /// An animation frame refers to a sprite not defined in the atlas.
+    UnknownFrame {
+        animation: String,
+        sprite: String,
+    },
+}
+
+impl Display for SpriteSheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpriteSheetError::Load(e) => write!(f, "cannot load sprite atlas: {}", e),
+            SpriteSheetError::Parse(e) => write!(f, "invalid sprite atlas: {}", e),
+            SpriteSheetError::UnknownSprite(name) => write!(f, "unknown sprite `{}`", name),
+            SpriteSheetError::UnknownTexture { sprite, texture } => {
+                write!(f, "sprite `{}` uses unknown texture {}", sprite, texture)
+            }
+            SpriteSheetError::UnknownFrame { animation, sprite } => write!(
+                f,
+                "animation `{}` uses unknown sprite `{}`",
+                animation, sprite
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SpriteSheetError {}
+
+impl From<serde_json::Error> for SpriteSheetError {
+    fn from(e: serde_json::Error) -> Self {
+        SpriteSheetError::Parse(e)
+    }
+}
+
+/// A texture of the atlas, cut into a grid of equally sized cells.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TextureDescriptor {
+    pub path: String,
+    /// Width and height of a cell, in pixels.
+    pub cell_size: (u32, u32),
+    /// Pixels between two cells, horizontally and vertically.
+    #[serde(default)]
+    pub gap: (u32, u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct SpriteDescriptor {
+    pub row: u32,
+    pub col: u32,
+    /// Index of the texture in `AtlasDescriptor::textures`.
+    #[serde(default)]
+    pub texture: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum FrameDescriptor {
+    /// A sprite shown for the animation's `frame_duration`.
+    Sprite(String),
+    Timed {
+        sprite: String,
+        duration: f32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AnimationDescriptor {
+    pub frames: Vec<FrameDescriptor>,
+    #[serde(default = "default_frame_duration")]
+    pub frame_duration: f32,
+    #[serde(default)]
+    pub loop_mode: LoopMode,
+}
+
+fn default_frame_duration() -> f32 {
+    0.25
+}
+
+/// The content of an atlas file such as `assets/config.json`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AtlasDescriptor {
+    pub textures: Vec<TextureDescriptor>,
+    #[serde(default)]
+    pub sprites: HashMap<String, SpriteDescriptor>,
+    #[serde(default)]
+    pub animations: HashMap<String, AnimationDescriptor>,
+}
+
+impl AtlasDescriptor {
+    pub fn from_json(json: &str) -> Result<Self, SpriteSheetError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// The position of every sprite in its texture.
+    pub fn sprites(&self) -> Result<HashMap<String, Sprite>, SpriteSheetError> {
+        self.sprites
+            .iter()
+            .map(|(name, sprite)| {
+                let texture = self.textures.get(sprite.texture).ok_or_else(|| {
+                    SpriteSheetError::UnknownTexture {
+                        sprite: name.clone(),
+                        texture: sprite.texture,
+                    }
+                })?;
+                let (w, h) = texture.cell_size;
+                let (gap_x, gap_y) = texture.gap;
+                let pos = Rect {
+                    x: (sprite.col * (w + gap_x)) as f32,
+                    y: (sprite.row * (h + gap_y)) as f32,
+                    w: w as f32,
+                    h: h as f32,
+                };
+                let spritesheet =
+                    u8::try_from(sprite.texture).map_err(|_| SpriteSheetError::UnknownTexture {
+                        sprite: name.clone(),
+                        texture: sprite.texture,
+                    })?;
+                Ok((name.clone(), Sprite { pos, spritesheet }))
+            })
+            .collect()
+    }
+
+    pub fn animations(&self) -> Result<HashMap<String, Animation>, SpriteSheetError> {
+        self.animations
+            .iter()
+            .map(|(name, animation)| {
+                let frames = animation
+                    .frames
+                    .iter()
+                    .map(|frame| {
+                        let (sprite, duration) = match frame {
+                            FrameDescriptor::Sprite(sprite) => {
+                                (sprite.clone(), animation.frame_duration)
+                            }
+                            FrameDescriptor::Timed { sprite, duration } => {
+                                (sprite.clone(), *duration)
+                            }
+                        };
+                        if !self.sprites.contains_key(&sprite) {
+                            return Err(SpriteSheetError::UnknownFrame {
+                                animation: name.clone(),
+                                sprite,
+                            });
+                        }
+                        Ok(AnimationFrame { sprite, duration })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((name.clone(), Animation::new(frames, animation.loop_mode)))
+            })
+            .collect()
+    }
+}
+
+impl SpriteSheet {
+    /// Builds a sprite sheet from a parsed atlas and its already loaded textures,
+    /// given in the same order as `AtlasDescriptor::textures`.
+    pub fn from_atlas(
+        atlas: &AtlasDescriptor,
+        textures: Vec<Texture2D>,
+    ) -> Result<Self, SpriteSheetError> {
+        Ok(Self {
+            textures,
+            sprites: atlas.sprites()?,
+            animations: atlas.animations()?,
+        })
+    }
+
+    /// Loads an atlas file and every texture it lists.
+    pub async fn load(path: &str) -> Result<Self, SpriteSheetError> {
+        let json = load_string(path)
+            .await
+            .map_err(|e| SpriteSheetError::Load(e.to_string()))?;
+        let atlas = AtlasDescriptor::from_json(&json)?;
+
+        let mut textures = Vec::new();
+        for texture in atlas.textures.iter() {
+            let loaded = load_texture(&texture.path)
+                .await
+                .map_err(|e| SpriteSheetError::Load(e.to_string()))?;
+            loaded.set_filter(FilterMode::Nearest);
+            textures.push(loaded);
+        }
+        Self::from_atlas(&atlas, textures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATLAS: &str = r#"{
+        "textures": [
+            { "path": "a.png", "cell_size": [12, 12], "gap": [1, 1] },
+            { "path": "b.png", "cell_size": [16, 16] }
+        ],
+        "sprites": {
+            "floor": { "row": 2, "col": 3 },
+            "torch_1": { "row": 0, "col": 1, "texture": 1 },
+            "torch_2": { "row": 0, "col": 2, "texture": 1 }
+        },
+        "animations": {
+            "torch": { "frames": ["torch_1", { "sprite": "torch_2", "duration": 1.0 }], "frame_duration": 0.5, "loop_mode": "ping_pong" }
+        }
+    }"#;
+
+    #[test]
+    fn test_atlas_descriptor() {
+        let atlas = AtlasDescriptor::from_json(ATLAS).unwrap();
+        let sprites = atlas.sprites().unwrap();
+
+        assert_eq!(sprites["floor"].pos, Rect::new(39., 26., 12., 12.));
+        assert_eq!(sprites["floor"].spritesheet, 0);
+        assert_eq!(sprites["torch_2"].pos, Rect::new(32., 0., 16., 16.));
+        assert_eq!(sprites["torch_2"].spritesheet, 1);
+
+        let torch = &atlas.animations().unwrap()["torch"];
+        assert_eq!(torch.loop_mode, LoopMode::PingPong);
+        assert_eq!(torch.duration(), 1.5);
+    }
+
+    #[test]
+    fn test_atlas_errors() {
+        let bad_texture = r#"{ "textures": [], "sprites": { "x": { "row": 0, "col": 0 } } }"#;
+        assert!(matches!(
+            AtlasDescriptor::from_json(bad_texture).unwrap().sprites(),
+            Err(SpriteSheetError::UnknownTexture { texture: 0, .. })
+        ));
+
+        let bad_frame = r#"{ "textures": [], "animations": { "a": { "frames": ["missing"] } } }"#;
+        assert!(matches!(
+            AtlasDescriptor::from_json(bad_frame).unwrap().animations(),
+            Err(SpriteSheetError::UnknownFrame { .. })
+        ));
+    }
+}
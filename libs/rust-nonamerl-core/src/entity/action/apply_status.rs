@@ -0,0 +1,139 @@
+#![allow(dead_code)]
+use crate::{
+    entity::{
+        event::WorldEvent,
+        status::{StatusEffect, StatusEffects},
+        world::{EntityKey, World},
+    },
+    Map, Tile,
+};
+
+use super::{Action, ActionResult};
+
+/// Puts the target under a status effect, see `StatusEffects::apply`.
+///
+/// This is how potions, traps and spells poison, blind or confuse an entity.
+#[derive(Debug)]
+pub struct ApplyStatusAction<T: Tile> {
+    pub target: EntityKey,
+    pub effect: StatusEffect,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Tile> ApplyStatusAction<T> {
+    pub fn new(target: EntityKey, effect: StatusEffect) -> Self {
+        Self {
+            target,
+            effect,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Tile> Action<T> for ApplyStatusAction<T> {
+    /// Fails if the target does not exist or the effect lasts no turn.
+    fn perform(&self, world: &World<T>, _map: &mut Map<T>) -> ActionResult<T> {
+        if self.effect.turns == 0 {
+            return ActionResult::Failed;
+        }
+        {
+            let mut entities = world.entities.borrow_mut();
+            let Some(target) = entities.get_mut(self.target) else {
+                return ActionResult::Failed;
+            };
+            if !target.has::<StatusEffects>() {
+                target.insert(StatusEffects::new());
+            }
+            target
+                .get_mut::<StatusEffects>()
+                .unwrap()
+                .apply(self.effect);
+        }
+        world.emit(WorldEvent::StatusApplied {
+            entity: self.target,
+            kind: self.effect.kind,
+        });
+        ActionResult::Done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::{
+            component::{Energy, Health, ACTION_COST},
+            status::StatusKind,
+        },
+        test_support::TestTile,
+        ActionQueue, Dimension2D, IntExtent2D,
+    };
+
+    #[test]
+    fn test_poison_ticks_and_expires() {
+        let mut world = World::<TestTile>::new();
+        let mut map = Map::new(IntExtent2D::new(0, 0, 1, 1), Dimension2D::new(1, 1));
+        let rat = world.spawn("Rat", |entity| {
+            entity.insert(Health::new(10));
+        });
+
+        let mut queue = ActionQueue::new();
+        queue.add(Box::new(ApplyStatusAction::new(
+            rat,
+            StatusEffect::new(StatusKind::Poison, 2, 3),
+        )));
+        queue.process_actions(&mut world, &mut map);
+        for _ in 0..3 {
+            queue.add_all(world.tick_statuses());
+            queue.process_actions(&mut world, &mut map);
+        }
+
+        let entities = world.entities.borrow();
+        let rat_entity = entities.get(rat).unwrap();
+        assert_eq!(rat_entity.get::<Health>().unwrap().current, 4);
+        assert!(rat_entity.get::<StatusEffects>().unwrap().is_empty());
+        let events = world.take_events();
+        assert_eq!(
+            events.first(),
+            Some(&WorldEvent::StatusApplied {
+                entity: rat,
+                kind: StatusKind::Poison
+            })
+        );
+        assert!(events.contains(&WorldEvent::StatusExpired {
+            entity: rat,
+            kind: StatusKind::Poison
+        }));
+    }
+
+    #[test]
+    fn test_haste_and_slow_change_energy() {
+        let world = World::<TestTile>::new();
+        let mut map = Map::new(IntExtent2D::new(0, 0, 1, 1), Dimension2D::new(1, 1));
+        let spawn = || {
+            world.spawn("Runner", |entity| {
+                entity.insert(Energy(0));
+            })
+        };
+        let normal = spawn();
+        let hasted = spawn();
+        let slowed = spawn();
+        for (entity, kind) in [(hasted, StatusKind::Haste), (slowed, StatusKind::Slow)] {
+            let effect = StatusEffect::new(kind, 10, ACTION_COST / 2);
+            ApplyStatusAction::new(entity, effect).perform(&world, &mut map);
+        }
+
+        // counts the actions of each entity over four turns
+        let mut actions = [0; 3];
+        for _ in 0..4 {
+            world.gain_energy();
+            for (i, entity) in [normal, hasted, slowed].into_iter().enumerate() {
+                while world.can_act(entity) {
+                    world.spend_energy(entity);
+                    actions[i] += 1;
+                }
+            }
+        }
+        assert_eq!(actions, [4, 6, 2]);
+    }
+}
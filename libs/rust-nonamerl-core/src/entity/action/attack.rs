@@ -1,10 +1,24 @@
 #![allow(dead_code)]
+use rand::Rng;
+
 use crate::{
-    entity::world::{EntityKey, World},
+    entity::{
+        component::Health,
+        equipment::{Equipment, EquipmentBonus},
+        event::WorldEvent,
+        level::Level,
+        world::{EntityKey, World},
+    },
     Map, Tile,
 };
 
-use super::Action;
+use super::{Action, ActionResult, DeathAction};
+
+/// Damage dealt by an entity with nothing equipped that deals damage.
+pub const UNARMED_DAMAGE: i32 = 1;
+
+/// Chance, from 0 to 1, that a melee attack lands.
+pub const MELEE_HIT_CHANCE: f32 = 0.8;
 
 /// This module contains the implementation of the `Attack` action for entities in the game.
 /// The `Attack` action allows an entity to attack another entity, dealing damage to it.
@@ -13,38 +27,208 @@ use super::Action;
 /// It takes a target entity as a parameter and returns a `ActionResult` indicating whether the attack was successful or not.
 ///
 /// The `Attack` action can be used by any entity that has the ability to attack, such as a player or a monster.
+///
+/// The outcome is reported through `World::take_events` as `WorldEvent::Hit`,
+/// `WorldEvent::Missed` and `WorldEvent::Killed`.
 #[derive(Debug)]
 pub struct AttackAction<T: Tile> {
     pub damage: i32,
     pub target: EntityKey,
+    /// The entity attacking, `None` for environmental damage.
+    pub attacker: Option<EntityKey>,
+    /// Chance, from 0 to 1, that the attack lands.
+    pub hit_chance: f32,
     _phantom: std::marker::PhantomData<T>,
 }
 
 impl<T: Tile> AttackAction<T> {
+    /// An attack that always lands, e.g. from a trap.
     pub fn new(damage: i32) -> Self {
         Self {
             damage,
             target: Default::default(),
+            attacker: None,
+            hit_chance: 1.,
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// An attack with the damage of everything `attacker` has equipped, or bare-handed,
+    /// plus the damage gained from its `Level`.
+    pub fn melee(world: &World<T>, attacker: EntityKey) -> Self {
+        let damage = match bonus_of(world, attacker).damage {
+            0 => UNARMED_DAMAGE,
+            damage => damage,
+        } + level_of(world, attacker).damage;
+        let mut attack = Self::new(damage).from(attacker);
+        attack.hit_chance = MELEE_HIT_CHANCE;
+        attack
+    }
+
     pub fn to(mut self, target: EntityKey) -> Self {
         self.target = target;
         self
     }
+
+    pub fn from(mut self, attacker: EntityKey) -> Self {
+        self.attacker = Some(attacker);
+        self
+    }
+}
+
+/// The stats `entity` gets from its `Equipment`.
+fn bonus_of<T: Tile>(world: &World<T>, entity: EntityKey) -> EquipmentBonus {
+    world
+        .entities
+        .borrow()
+        .get(entity)
+        .and_then(|entity| entity.get::<Equipment>())
+        .map(|equipment| equipment.bonus(&world.items.borrow()))
+        .unwrap_or_default()
+}
+
+/// The `Level` of `entity`, level 1 with no bonus if it does not level up.
+fn level_of<T: Tile>(world: &World<T>, entity: EntityKey) -> Level {
+    world
+        .entities
+        .borrow()
+        .get(entity)
+        .and_then(|entity| entity.get::<Level>().copied())
+        .unwrap_or_default()
 }
 
 impl<T: Tile> Action<T> for AttackAction<T> {
-    fn perform(&self, world: &World<T>, _map: &mut Map<T>) {
+    /// Rolls the hit chance, then removes the damage, reduced by the defense of the
+    /// target's equipment and level, from the target's `Health`.
+    ///
+    /// The attack fails if the target has no `Health` or is already dead. A killing blow
+    /// is followed by a `DeathAction`.
+    fn perform(&self, world: &World<T>, _map: &mut Map<T>) -> ActionResult<T> {
         if self.target == Default::default() {
             panic!("Target not set");
         }
-        let entities = world.entities.borrow();
-        let target = entities.get(self.target).unwrap();
-        println!(
-            "Attack action to target {:?} inflicting {} damage",
-            target, self.damage
+        let alive = world
+            .entities
+            .borrow()
+            .get(self.target)
+            .and_then(|target| target.get::<Health>())
+            .is_some_and(|health| !health.is_dead());
+        if !alive {
+            return ActionResult::Failed;
+        }
+
+        if world.rng.borrow_mut().gen::<f32>() >= self.hit_chance {
+            world.emit(WorldEvent::Missed {
+                attacker: self.attacker,
+                target: self.target,
+            });
+            return ActionResult::Done;
+        }
+
+        let defense = bonus_of(world, self.target).defense + level_of(world, self.target).defense;
+        let damage = (self.damage - defense).max(0);
+
+        let dead = {
+            let mut entities = world.entities.borrow_mut();
+            let health = entities
+                .get_mut(self.target)
+                .and_then(|target| target.get_mut::<Health>())
+                .unwrap();
+            health.current = (health.current - damage).max(0);
+            health.is_dead()
+        };
+        world.emit(WorldEvent::Hit {
+            attacker: self.attacker,
+            target: self.target,
+            damage,
+            blocked: self.damage - damage,
+        });
+        if dead {
+            world.emit(WorldEvent::Killed {
+                attacker: self.attacker,
+                target: self.target,
+            });
+            return ActionResult::Then(vec![Box::new(
+                DeathAction::new(self.target).by(self.attacker),
+            )]);
+        }
+        ActionResult::Done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::item::{Damage, Defense, ItemBuilder, ItemKind, Weapon},
+        test_support::TestTile,
+        Dimension2D, IntExtent2D,
+    };
+
+    fn weapon(world: &World<TestTile>, damage: Option<i32>, defense: Option<i32>) -> Equipment {
+        let kind = ItemKind::Weapon(Weapon {
+            damage: damage.map(|value| Damage { value }),
+            defense: defense.map(|value| Defense { value }),
+        });
+        let item = ItemBuilder::new("Weapon".to_owned(), kind).build(world);
+        let mut equipment = Equipment::new();
+        equipment.equip(item, None, &world.items.borrow()).unwrap();
+        equipment
+    }
+
+    #[test]
+    fn test_attack_resolution() {
+        let world = World::<TestTile>::with_seed(7);
+        let mut map = Map::new(IntExtent2D::new(0, 0, 1, 1), Dimension2D::new(1, 1));
+        let sword = weapon(&world, Some(6), None);
+        let shield = weapon(&world, None, Some(2));
+        let attacker = world.spawn("Attacker", |entity| {
+            entity.insert(sword.clone());
+        });
+        let target = world.spawn("Target", |entity| {
+            entity.insert(Health::new(8));
+            entity.insert(shield.clone());
+        });
+
+        let mut attack = AttackAction::melee(&world, attacker).to(target);
+        assert_eq!(attack.damage, 6);
+        attack.hit_chance = 0.;
+        attack.perform(&world, &mut map);
+        assert_eq!(
+            world.take_events(),
+            vec![WorldEvent::Missed {
+                attacker: Some(attacker),
+                target
+            }]
+        );
+
+        attack.hit_chance = 1.;
+        attack.perform(&world, &mut map);
+        attack.perform(&world, &mut map);
+        let hit = WorldEvent::Hit {
+            attacker: Some(attacker),
+            target,
+            damage: 4,
+            blocked: 2,
+        };
+        assert_eq!(
+            world.take_events(),
+            vec![
+                hit.clone(),
+                hit,
+                WorldEvent::Killed {
+                    attacker: Some(attacker),
+                    target
+                }
+            ]
+        );
+        assert_eq!(
+            world.entities.borrow().get(target).unwrap().get::<Health>(),
+            Some(&Health { current: 0, max: 8 })
         );
+        assert!(matches!(
+            attack.perform(&world, &mut map),
+            ActionResult::Failed
+        ));
     }
 }
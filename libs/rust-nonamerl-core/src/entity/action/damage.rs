@@ -0,0 +1,128 @@
+#![allow(dead_code)]
+use crate::{
+    entity::{
+        component::Health,
+        event::WorldEvent,
+        world::{EntityKey, World},
+    },
+    Map, Tile,
+};
+
+use super::{Action, ActionResult, DeathAction};
+
+/// Removes `amount` health from the target, ignoring its defense.
+///
+/// This is the damage of poison, fire and gas: nothing attacks, so nothing can miss or be
+/// blocked. Use `AttackAction` for blows.
+#[derive(Debug)]
+pub struct DamageAction<T: Tile> {
+    pub target: EntityKey,
+    pub amount: i32,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Tile> DamageAction<T> {
+    pub fn new(target: EntityKey, amount: i32) -> Self {
+        Self {
+            target,
+            amount,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Tile> Action<T> for DamageAction<T> {
+    /// Fails if the target has no `Health` or is already dead. Emits `WorldEvent::Hurt` with
+    /// the health actually lost, and `WorldEvent::Killed` followed by a `DeathAction` when
+    /// the target dies.
+    fn perform(&self, world: &World<T>, _map: &mut Map<T>) -> ActionResult<T> {
+        let (damage, dead) = {
+            let mut entities = world.entities.borrow_mut();
+            let Some(health) = entities
+                .get_mut(self.target)
+                .and_then(|target| target.get_mut::<Health>())
+                .filter(|health| !health.is_dead())
+            else {
+                return ActionResult::Failed;
+            };
+            let before = health.current;
+            health.current = (health.current - self.amount.max(0)).max(0);
+            (before - health.current, health.is_dead())
+        };
+        world.emit(WorldEvent::Hurt {
+            entity: self.target,
+            damage,
+        });
+        if dead {
+            world.emit(WorldEvent::Killed {
+                attacker: None,
+                target: self.target,
+            });
+            return ActionResult::Then(vec![Box::new(DeathAction::new(self.target))]);
+        }
+        ActionResult::Done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::{
+            equipment::Equipment,
+            item::{Defense, ItemBuilder, ItemKind, Weapon},
+        },
+        test_support::TestTile,
+        Dimension2D, IntExtent2D,
+    };
+
+    #[test]
+    fn test_damage_ignores_defense() {
+        let world = World::<TestTile>::new();
+        let mut map = Map::new(IntExtent2D::new(0, 0, 1, 1), Dimension2D::new(1, 1));
+        let kind = ItemKind::Weapon(Weapon {
+            damage: None,
+            defense: Some(Defense { value: 5 }),
+        });
+        let shield = ItemBuilder::new("Shield".to_owned(), kind).build(&world);
+        let mut equipment = Equipment::new();
+        equipment
+            .equip(shield, None, &world.items.borrow())
+            .unwrap();
+        let target = world.spawn("Target", |entity| {
+            entity.insert(Health::new(3));
+            entity.insert(equipment.clone());
+        });
+
+        let damage = DamageAction::new(target, 2);
+        assert!(matches!(
+            damage.perform(&world, &mut map),
+            ActionResult::Done
+        ));
+        assert!(matches!(
+            damage.perform(&world, &mut map),
+            ActionResult::Then(_)
+        ));
+        assert_eq!(
+            world.take_events(),
+            vec![
+                WorldEvent::Hurt {
+                    entity: target,
+                    damage: 2
+                },
+                WorldEvent::Hurt {
+                    entity: target,
+                    damage: 1
+                },
+                WorldEvent::Killed {
+                    attacker: None,
+                    target
+                },
+            ]
+        );
+        assert!(matches!(
+            damage.perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+    }
+}
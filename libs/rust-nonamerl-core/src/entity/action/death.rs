@@ -0,0 +1,192 @@
+#![allow(dead_code)]
+use crate::{
+    entity::{
+        component::{Blocking, Corpse, Faction, Position, XpReward},
+        entity::Entity,
+        equipment::Equipment,
+        event::WorldEvent,
+        inventory::Inventory,
+        item::{ItemBuilder, ItemKind},
+        world::{EntityKey, ItemKey, World},
+    },
+    Map, MapCommand, Tile,
+};
+
+use super::{Action, ActionResult};
+
+/// Removes a dead entity from the world.
+///
+/// The items it carried and held are dropped on its cell, a `Corpse` item is left there if the
+/// entity has one, its `XpReward` is awarded to the killer (see `World::award_xp`) and `WorldEvent::Died` is emitted.
+/// The death of the player ends the run, see `World::is_game_over`. The player is not removed,
+/// so the game can still read it: it only loses its `Position` and `Blocking`, and keeps its items.
+#[derive(Debug)]
+pub struct DeathAction<T: Tile> {
+    pub entity: EntityKey,
+    pub killer: Option<EntityKey>,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Tile> DeathAction<T> {
+    pub fn new(entity: EntityKey) -> Self {
+        Self {
+            entity,
+            killer: None,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    pub fn by(mut self, killer: Option<EntityKey>) -> Self {
+        self.killer = killer;
+        self
+    }
+}
+
+impl<T: Tile> DeathAction<T> {
+    /// Takes the dead player off the map, returning it as it was when it died.
+    fn lay_down_player(&self, world: &World<T>) -> Option<Entity> {
+        let dead = {
+            let mut entities = world.entities.borrow_mut();
+            let player = entities.get_mut(self.entity)?;
+            let dead = player.clone();
+            player.remove::<Position>();
+            player.remove::<Blocking>();
+            dead
+        };
+        world.reindex(self.entity);
+        Some(dead)
+    }
+}
+
+impl<T: Tile> Action<T> for DeathAction<T> {
+    fn perform(&self, world: &World<T>, map: &mut Map<T>) -> ActionResult<T> {
+        let is_player = world
+            .entities
+            .borrow()
+            .get(self.entity)
+            .is_some_and(|entity| entity.get::<Faction>() == Some(&Faction::Player));
+        let dead = if is_player {
+            self.lay_down_player(world)
+        } else {
+            world.despawn(self.entity)
+        };
+        let Some(dead) = dead else {
+            return ActionResult::Failed;
+        };
+        let pos = dead.get::<Position>().map(|pos| pos.0);
+
+        if let Some(pos) = pos.filter(|_| !is_player) {
+            let mut drops: Vec<ItemKey> = dead
+                .get::<Inventory>()
+                .map(|inventory| inventory.items().collect())
+                .unwrap_or_default();
+            for item in dead
+                .get::<Equipment>()
+                .map(Equipment::items)
+                .unwrap_or_default()
+            {
+                if !drops.contains(&item) {
+                    drops.push(item);
+                }
+            }
+            if let Some(Corpse(name)) = dead.get::<Corpse>() {
+                drops.push(ItemBuilder::new(name.clone(), ItemKind::Misc).build(world));
+            }
+            for item in drops {
+                MapCommand::AddItem(pos, item).apply(map);
+            }
+        }
+
+        if let (Some(killer), Some(XpReward(xp))) = (self.killer, dead.get::<XpReward>()) {
+            world.award_xp(killer, *xp);
+        }
+
+        if is_player {
+            world.end_run();
+        }
+        world.emit(WorldEvent::Died {
+            entity: self.entity,
+            name: dead.name().to_owned(),
+            killer: self.killer,
+            position: pos,
+        });
+        ActionResult::Done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::{
+            component::{Health, Xp},
+            item::{Damage, Weapon},
+        },
+        test_support::ItemTile,
+        ActionQueue, AttackAction, Dimension2D, IntExtent2D, IntVector2, ItemContainer,
+    };
+
+    #[test]
+    fn test_death_pipeline() {
+        let mut world = World::<ItemTile>::with_seed(0);
+        let mut map = Map::new(IntExtent2D::new(0, 0, 2, 1), Dimension2D::new(1, 1));
+        map.set(1, 0, ItemTile::default());
+        let club = ItemKind::Weapon(Weapon {
+            damage: Some(Damage { value: 2 }),
+            defense: None,
+        });
+        let club = ItemBuilder::new("Club".to_owned(), club).build(&world);
+        let mut equipment = Equipment::new();
+        equipment.equip(club, None, &world.items.borrow()).unwrap();
+        let player = world.spawn("Player", |entity| {
+            entity.insert(Xp(1));
+            entity.insert(Faction::Player);
+            entity.insert(Health::new(5));
+            entity.insert(Position(IntVector2::new(0, 0)));
+            entity.insert(Blocking);
+        });
+        let orc = world.spawn("Orc", |entity| {
+            entity.insert(Position(IntVector2::new(1, 0)));
+            entity.insert(Health::new(3));
+            entity.insert(Xp(4));
+            entity.insert(XpReward(10));
+            entity.insert(equipment.clone());
+            entity.insert(Corpse("Orc corpse".to_owned()));
+        });
+
+        let mut queue = ActionQueue::new();
+        queue.add(Box::new(AttackAction::new(5).from(player).to(orc)));
+        queue.process_actions(&mut world, &mut map);
+
+        assert!(world.entities.borrow().get(orc).is_none());
+        assert!(world.spatial.borrow().at(IntVector2::new(1, 0)).is_empty());
+        let dropped = map.get(1, 0).unwrap().items().to_vec();
+        assert_eq!(dropped.len(), 2);
+        assert_eq!(dropped[0], club);
+        assert_eq!(
+            world.items.borrow().get(dropped[1]).unwrap().name,
+            "Orc corpse"
+        );
+        assert_eq!(
+            world.entities.borrow().get(player).unwrap().get::<Xp>(),
+            Some(&Xp(11))
+        );
+        assert!(matches!(
+            world.take_events().last(),
+            Some(WorldEvent::Died { name, killer: Some(k), .. }) if name == "Orc" && *k == player
+        ));
+        assert!(!world.is_game_over());
+
+        queue.add(Box::new(AttackAction::new(5).to(player)));
+        queue.process_actions(&mut world, &mut map);
+        assert!(world.is_game_over());
+
+        // the next frame still finds the player, off the map
+        let entities = world.entities.borrow();
+        let dead = entities.get(player).unwrap();
+        assert_eq!(dead.get::<Xp>(), Some(&Xp(11)));
+        assert!(dead.get::<Health>().unwrap().is_dead());
+        assert!(!dead.has::<Position>() && !dead.has::<Blocking>());
+        assert!(world.spatial.borrow().is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+#![allow(dead_code)]
+use crate::{
+    entity::{
+        component::Position,
+        world::{EntityKey, World},
+    },
+    IntVector2, Map, MapCommand, Tile, Vec2,
+};
+
+use super::{Action, ActionResult};
+
+/// Damages the tile next to the digger, destroying it once its durability runs out.
+#[derive(Debug)]
+pub struct DigAction<T: Tile> {
+    pub dx: IntVector2,
+    pub digger: EntityKey,
+    /// Durability removed from the tile.
+    pub power: u32,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Tile> DigAction<T> {
+    pub fn new(dx: IntVector2, digger: EntityKey, power: u32) -> Self {
+        Self {
+            dx,
+            digger,
+            power,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Tile> Action<T> for DigAction<T> {
+    /// Applies a `MapCommand::DamageTile` to the tile at the digger's position plus `dx`.
+    ///
+    /// The dig fails if the digger is unknown or has no `Position`, if there is no tile
+    /// there or if the tile cannot be damaged.
+    fn perform(&self, world: &World<T>, map: &mut Map<T>) -> ActionResult<T> {
+        let Some(pos) = world
+            .entities
+            .borrow()
+            .get(self.digger)
+            .and_then(|digger| digger.get::<Position>())
+            .map(|pos| pos.0)
+        else {
+            return ActionResult::Failed;
+        };
+        let target = IntVector2::new(pos.x() + self.dx.x(), pos.y() + self.dx.y());
+
+        match MapCommand::DamageTile(target, self.power).apply(map) {
+            Some(_) => ActionResult::Done,
+            None => ActionResult::Failed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Dimension2D, FovOccluder, IntExtent2D, ItemContainer, TileTransition, Visible, Visited,
+        Walkable,
+    };
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum TestTile {
+        Floor,
+        Wall(u32),
+    }
+    impl Tile for TestTile {
+        fn durability(&self) -> Option<u32> {
+            match self {
+                TestTile::Wall(durability) => Some(*durability),
+                TestTile::Floor => None,
+            }
+        }
+        fn set_durability(&mut self, durability: u32) {
+            if let TestTile::Wall(d) = self {
+                *d = durability;
+            }
+        }
+        fn transition(&self, transition: TileTransition) -> Option<Self> {
+            match (self, transition) {
+                (TestTile::Wall(_), TileTransition::Destroyed) => Some(TestTile::Floor),
+                _ => None,
+            }
+        }
+    }
+    impl Visible for TestTile {}
+    impl Visited for TestTile {}
+    impl FovOccluder for TestTile {}
+    impl Walkable for TestTile {}
+    impl ItemContainer for TestTile {}
+
+    #[test]
+    fn test_dig_through_wall() {
+        let world = World::<TestTile>::new();
+        let mut map = Map::new(IntExtent2D::new(0, 0, 3, 1), Dimension2D::new(1, 1));
+        map.set(0, 0, TestTile::Floor);
+        map.set(1, 0, TestTile::Wall(5));
+        let digger = world.spawn("Digger", |entity| {
+            entity.insert(Position(IntVector2::new(0, 0)));
+        });
+
+        let dig = DigAction::new(IntVector2::new(1, 0), digger, 3);
+        assert!(matches!(dig.perform(&world, &mut map), ActionResult::Done));
+        assert_eq!(map.get(1, 0), Some(&TestTile::Wall(2)));
+        assert!(matches!(dig.perform(&world, &mut map), ActionResult::Done));
+        assert_eq!(map.get(1, 0), Some(&TestTile::Floor));
+        assert!(matches!(
+            dig.perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+
+        let outside = DigAction::new(IntVector2::new(-1, 0), digger, 3);
+        assert!(matches!(
+            outside.perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+
+        let nowhere = world.spawn("Ghost", |_| {});
+        let dig = DigAction::new(IntVector2::new(1, 0), nowhere, 3);
+        assert!(matches!(
+            dig.perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+        world.despawn(digger);
+        let dig = DigAction::new(IntVector2::new(1, 0), digger, 3);
+        assert!(matches!(
+            dig.perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+    }
+}
@@ -0,0 +1,66 @@
+#![allow(dead_code)]
+use crate::{
+    entity::{
+        component::Position,
+        equipment::Equipment,
+        event::WorldEvent,
+        inventory::Inventory,
+        world::{EntityKey, ItemKey, World},
+    },
+    Map, MapCommand, Tile,
+};
+
+use super::{Action, ActionResult};
+
+/// Moves an item from the entity's `Inventory` onto the tile it stands on, taking it off
+/// first if it is equipped.
+#[derive(Debug)]
+pub struct DropAction<T: Tile> {
+    pub entity: EntityKey,
+    pub item: ItemKey,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Tile> DropAction<T> {
+    pub fn new(entity: EntityKey, item: ItemKey) -> Self {
+        Self {
+            entity,
+            item,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Tile> Action<T> for DropAction<T> {
+    /// Fails if the item is not carried or there is no tile to drop it on.
+    fn perform(&self, world: &World<T>, map: &mut Map<T>) -> ActionResult<T> {
+        let mut entities = world.entities.borrow_mut();
+        let Some(entity) = entities.get_mut(self.entity) else {
+            return ActionResult::Failed;
+        };
+        let Some(pos) = entity.get::<Position>().map(|pos| pos.0) else {
+            return ActionResult::Failed;
+        };
+        let carried = entity
+            .get::<Inventory>()
+            .is_some_and(|inventory| inventory.contains(self.item));
+        if !carried || MapCommand::AddItem(pos, self.item).apply(map).is_none() {
+            return ActionResult::Failed;
+        }
+        entity.get_mut::<Inventory>().unwrap().remove(self.item);
+        if entity
+            .get_mut::<Equipment>()
+            .is_some_and(|equipment| equipment.remove(self.item))
+        {
+            world.emit(WorldEvent::Unequipped {
+                entity: self.entity,
+                item: self.item,
+            });
+        }
+        world.emit(WorldEvent::Dropped {
+            entity: self.entity,
+            item: self.item,
+        });
+        ActionResult::Done
+    }
+}
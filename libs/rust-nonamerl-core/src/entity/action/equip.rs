@@ -1,22 +1,28 @@
 #![allow(dead_code)]
 use crate::{
     entity::{
-        property::Property,
+        component::Position,
+        equipment::{EquipSlot, Equipment},
+        event::WorldEvent,
+        inventory::Inventory,
         world::{EntityKey, ItemKey, World},
     },
-    Map, Tile,
+    Map, MapCommand, Tile, Vec2,
 };
 
-use super::Action;
+use super::{unequip::put_away, Action, ActionResult};
 
-/// Equips the target entity with the given item.
-/// The item must be in the inventory of the target entity.
-/// The item must be equippable.
-/// The target entity must not already be equipped with an item of the same type.
+/// Equips the target entity with the given item, see `Equipment::equip`.
+///
+/// If the target has an `Inventory`, the item must be in it. Otherwise the item must lie on
+/// the target's cell, and is picked up from there. Items taken off to make room stay in the
+/// inventory, or are dropped on the target's cell if it has none.
 #[derive(Debug, Clone, Copy)]
 pub struct EquipAction<T: Tile> {
     pub item: ItemKey,
     pub target: EntityKey,
+    /// The slot to use, `None` for the first free slot the item fits.
+    pub slot: Option<EquipSlot>,
     _phantom: std::marker::PhantomData<T>,
 }
 
@@ -25,18 +31,156 @@ impl<T: Tile> EquipAction<T> {
         Self {
             item,
             target,
+            slot: None,
             _phantom: std::marker::PhantomData,
         }
     }
+
+    pub fn in_slot(mut self, slot: EquipSlot) -> Self {
+        self.slot = Some(slot);
+        self
+    }
 }
 
 impl<T: Tile> Action<T> for EquipAction<T> {
-    fn perform(&self, world: &World<T>, _map: &mut Map<T>) {
-        let mut entities = world.entities.borrow_mut();
-        let target = entities.get_mut(self.target).unwrap();
-        let items = world.items.borrow();
-        let item = items.get(self.item).unwrap();
-        target.add_property(Property::Equip(self.item));
-        println!("Equip action to target {:?} with item {:?}", target, item);
+    fn perform(&self, world: &World<T>, map: &mut Map<T>) -> ActionResult<T> {
+        let removed = {
+            let mut entities = world.entities.borrow_mut();
+            let Some(target) = entities.get_mut(self.target) else {
+                return ActionResult::Failed;
+            };
+            let ground = match (target.get::<Inventory>(), target.get::<Position>()) {
+                (Some(inventory), _) if inventory.contains(self.item) => None,
+                (None, Some(Position(pos)))
+                    if map
+                        .get(pos.x(), pos.y())
+                        .is_some_and(|tile| tile.items().contains(&self.item)) =>
+                {
+                    Some(*pos)
+                }
+                _ => return ActionResult::Failed,
+            };
+            if !target.has::<Equipment>() {
+                target.insert(Equipment::new());
+            }
+            let equipment = target.get_mut::<Equipment>().unwrap();
+            let removed = match equipment.equip(self.item, self.slot, &world.items.borrow()) {
+                Ok(removed) => removed,
+                Err(_) => return ActionResult::Failed,
+            };
+            if let Some(pos) = ground {
+                MapCommand::RemoveItem(pos, self.item).apply(map);
+            }
+            removed
+        };
+
+        for item in removed {
+            put_away(world, map, self.target, item);
+        }
+        world.emit(WorldEvent::Equipped {
+            entity: self.target,
+            item: self.item,
+        });
+        ActionResult::Done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::{
+            component::Position,
+            item::{Damage, ItemBehaviour, ItemBuilder, ItemKind, Weapon},
+        },
+        test_support::ItemTile,
+        Dimension2D, IntExtent2D, IntVector2, ItemContainer, UnequipAction,
+    };
+
+    fn weapon(world: &World<ItemTile>, name: &str, two_handed: bool) -> ItemKey {
+        let kind = ItemKind::Weapon(Weapon {
+            damage: Some(Damage { value: 3 }),
+            defense: None,
+        });
+        let builder = ItemBuilder::new(name.to_owned(), kind);
+        if two_handed {
+            builder.add_behaviour(ItemBehaviour::TwoHanded).build(world)
+        } else {
+            builder.build(world)
+        }
+    }
+
+    #[test]
+    fn test_equip_and_unequip() {
+        let world = World::<ItemTile>::new();
+        let mut map = Map::new(IntExtent2D::new(0, 0, 1, 1), Dimension2D::new(1, 1));
+        map.set(0, 0, ItemTile::default());
+        let sword = weapon(&world, "Sword", false);
+        let axe = weapon(&world, "Axe", true);
+        let carried = weapon(&world, "Dagger", false);
+        let player = world.spawn("Player", |entity| {
+            let mut inventory = Inventory::new(5, 100);
+            inventory.add(carried, &world.items.borrow()).unwrap();
+            entity.insert(inventory);
+        });
+        let orc = world.spawn("Orc", |entity| {
+            entity.insert(Position(IntVector2::new(0, 0)));
+        });
+
+        // the player can only equip what it carries
+        assert!(matches!(
+            EquipAction::new(sword, player).perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+        EquipAction::new(carried, player).perform(&world, &mut map);
+
+        // the orc has no inventory, so it picks up what lies on its cell
+        assert!(matches!(
+            EquipAction::new(carried, orc).perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+        map.add_item(0, 0, sword);
+        map.add_item(0, 0, axe);
+        EquipAction::new(sword, orc).perform(&world, &mut map);
+        assert_eq!(map.get(0, 0).unwrap().items(), &[axe]);
+        EquipAction::new(axe, orc).perform(&world, &mut map);
+        assert_eq!(map.get(0, 0).unwrap().items(), &[sword]);
+        UnequipAction::new(orc, EquipSlot::OffHand).perform(&world, &mut map);
+        assert_eq!(map.get(0, 0).unwrap().items(), &[sword, axe]);
+
+        let entities = world.entities.borrow();
+        let equipment = entities.get(player).unwrap().get::<Equipment>().unwrap();
+        assert_eq!(equipment.get(EquipSlot::MainHand), Some(carried));
+        assert!(entities
+            .get(orc)
+            .unwrap()
+            .get::<Equipment>()
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            world.take_events(),
+            vec![
+                WorldEvent::Equipped {
+                    entity: player,
+                    item: carried
+                },
+                WorldEvent::Equipped {
+                    entity: orc,
+                    item: sword
+                },
+                WorldEvent::Unequipped {
+                    entity: orc,
+                    item: sword
+                },
+                WorldEvent::Equipped {
+                    entity: orc,
+                    item: axe
+                },
+                WorldEvent::Unequipped {
+                    entity: orc,
+                    item: axe
+                },
+            ]
+        );
     }
 }
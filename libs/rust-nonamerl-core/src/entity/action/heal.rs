@@ -0,0 +1,54 @@
+#![allow(dead_code)]
+use crate::{
+    entity::{
+        component::Health,
+        event::WorldEvent,
+        world::{EntityKey, World},
+    },
+    Map, Tile,
+};
+
+use super::{Action, ActionResult};
+
+/// Restores `amount` health to the target, up to its maximum.
+#[derive(Debug)]
+pub struct HealAction<T: Tile> {
+    pub target: EntityKey,
+    pub amount: i32,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Tile> HealAction<T> {
+    pub fn new(target: EntityKey, amount: i32) -> Self {
+        Self {
+            target,
+            amount,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Tile> Action<T> for HealAction<T> {
+    /// Fails if the target has no `Health` or is dead. Emits `WorldEvent::Healed` with
+    /// the health actually restored.
+    fn perform(&self, world: &World<T>, _map: &mut Map<T>) -> ActionResult<T> {
+        let healed = {
+            let mut entities = world.entities.borrow_mut();
+            let Some(health) = entities
+                .get_mut(self.target)
+                .and_then(|target| target.get_mut::<Health>())
+                .filter(|health| !health.is_dead())
+            else {
+                return ActionResult::Failed;
+            };
+            let before = health.current;
+            health.current = (health.current + self.amount).min(health.max);
+            health.current - before
+        };
+        world.emit(WorldEvent::Healed {
+            entity: self.target,
+            amount: healed,
+        });
+        ActionResult::Done
+    }
+}
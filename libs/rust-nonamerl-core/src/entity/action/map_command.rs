@@ -0,0 +1,25 @@
+#![allow(dead_code)]
+use crate::{entity::world::World, Map, MapCommand, Tile};
+
+use super::{Action, ActionResult};
+
+/// Applies a `MapCommand` as part of the turn, e.g. a door opened by a pressure plate.
+#[derive(Debug)]
+pub struct MapCommandAction<T: Tile> {
+    pub command: MapCommand<T>,
+}
+
+impl<T: Tile> MapCommandAction<T> {
+    pub fn new(command: MapCommand<T>) -> Self {
+        Self { command }
+    }
+}
+
+impl<T: Tile> Action<T> for MapCommandAction<T> {
+    fn perform(&self, _world: &World<T>, map: &mut Map<T>) -> ActionResult<T> {
+        match self.command.clone().apply(map) {
+            Some(_) => ActionResult::Done,
+            None => ActionResult::Failed,
+        }
+    }
+}
@@ -1,26 +1,70 @@
+use std::fmt::Debug;
+
 use crate::{Map, Tile};
 
 use super::world::World;
 
+/// The outcome of performing an `Action`.
+pub enum ActionResult<T: Tile> {
+    Done,
+    /// The action could not be carried out and had no effect.
+    Failed,
+    /// The action is replaced by these actions, performed next and in order.
+    Then(Vec<Box<dyn Action<T>>>),
+}
+
+impl<T: Tile> Debug for ActionResult<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActionResult::Done => write!(f, "Done"),
+            ActionResult::Failed => write!(f, "Failed"),
+            ActionResult::Then(actions) => write!(f, "Then({} actions)", actions.len()),
+        }
+    }
+}
+
 pub trait Action<T: Tile> {
-    fn perform(&self, world: &World<T>, map: &mut Map<T>);
+    fn perform(&self, world: &World<T>, map: &mut Map<T>) -> ActionResult<T>;
 }
 
 #[derive(Debug)]
 pub struct DummyAction {}
 
 impl<T: Tile> Action<T> for DummyAction {
-    fn perform(&self, _world: &World<T>, _map: &mut Map<T>) {
+    fn perform(&self, _world: &World<T>, _map: &mut Map<T>) -> ActionResult<T> {
         println!("Dummy action");
+        ActionResult::Done
     }
 }
 
+pub mod apply_status;
 pub mod attack;
+pub mod damage;
+pub mod death;
+pub mod dig;
+pub mod drop;
 pub mod equip;
+pub mod heal;
+pub mod map_command;
 pub mod move_entity;
+pub mod pick_up;
 pub mod queue;
+pub mod teleport;
+pub mod unequip;
+pub mod use_item;
 
+pub use apply_status::ApplyStatusAction;
 pub use attack::AttackAction;
+pub use damage::DamageAction;
+pub use death::DeathAction;
+pub use dig::DigAction;
+pub use drop::DropAction;
 pub use equip::EquipAction;
+pub use heal::HealAction;
+pub use map_command::MapCommandAction;
 pub use move_entity::MoveAction;
+pub use pick_up::PickUpAction;
 pub use queue::ActionQueue;
+pub use teleport::TeleportAction;
+pub use unequip::UnequipAction;
+pub use use_item::UseItemAction;
@@ -1,11 +1,16 @@
 #![allow(dead_code)]
+use rand::{seq::SliceRandom, Rng};
+
 use crate::{
-    entity::world::{EntityKey, World},
-    property::Property,
-    IntVector2, Map, Tile, Vec2,
+    entity::{
+        component::{Blocking, Faction, Position},
+        status::{StatusEffects, StatusKind, CONFUSED_STUMBLE_CHANCE},
+        world::{EntityKey, World},
+    },
+    IntVector2, Map, Tile, TriggerEvent, Vec2,
 };
 
-use super::Action;
+use super::{Action, ActionResult, AttackAction};
 
 /// Moves the target entity by the given delta.
 #[derive(Debug)]
@@ -29,11 +34,11 @@ impl<T: Tile> MoveAction<T> {
 impl<T: Tile> Action<T> for MoveAction<T> {
     /// Moves the target entity by the given delta.
     ///
-    /// If the target entity does not have a position property, this function panics.
-    ///
-    /// If the target entity is not found, this function panics.
-    ///
-    /// If the target entity is not on a walkable tile, it is not moved.
+    /// The move fails if the destination is not a walkable tile or is occupied by a
+    /// blocking entity. Moving into an entity of a hostile faction turns the move into
+    /// an `AttackAction` against it. A successful move fires the `Leave` triggers of the
+    /// cell left and the `Enter` triggers of the cell reached; their actions follow the move.
+    /// A `Confused` entity may move in a random direction instead.
     ///
     /// # Arguments
     ///
@@ -43,34 +48,184 @@ impl<T: Tile> Action<T> for MoveAction<T> {
     ///
     /// # Panics
     ///
-    /// This function panics if the target entity does not have a position property.
+    /// This function panics if the target entity does not have a position component.
     ///
     /// This function panics if the target entity is not found.
     ///
-    /// This function panics if the target entity is not on a walkable tile.
-    ///
-    fn perform(&self, world: &World<T>, map: &mut Map<T>) {
+    fn perform(&self, world: &World<T>, map: &mut Map<T>) -> ActionResult<T> {
         if self.target == Default::default() {
             panic!("Target not set");
         }
-        let mut entities = world.entities.borrow_mut();
-        let target = entities.get_mut(self.target);
-        let target = target.unwrap();
-        let property = target.get_property_mut(Property::POSITION);
-
-        if property.is_none() {
-            panic!("Target does not have position property");
+        let (pos, faction, confused) = {
+            let entities = world.entities.borrow();
+            let target = entities.get(self.target).unwrap();
+            let pos = match target.get::<Position>() {
+                Some(pos) => pos.0,
+                None => panic!("Target does not have position component"),
+            };
+            let confused = target
+                .get::<StatusEffects>()
+                .is_some_and(|effects| effects.has(StatusKind::Confused));
+            (pos, target.get::<Faction>().copied(), confused)
+        };
+        let mut dx = self.dx;
+        if confused {
+            let mut rng = world.rng.borrow_mut();
+            if rng.gen::<f32>() < CONFUSED_STUMBLE_CHANCE {
+                let (x, y) = *[(0, -1), (0, 1), (-1, 0), (1, 0)]
+                    .choose(&mut *rng)
+                    .unwrap();
+                dx = IntVector2::new(x, y);
+            }
         }
+        let desired_pos = IntVector2::new(pos.x() + dx.x(), pos.y() + dx.y());
 
-        if let Some(Property::Position(pos)) = property {
-            let desired_pos = IntVector2::new(pos.x() + self.dx.x(), pos.y() + self.dx.y());
+        if !map.is_walkable(desired_pos.x(), desired_pos.y()) {
+            return ActionResult::Failed;
+        }
 
-            if let Some(tile) = map.get(desired_pos.x(), desired_pos.y()) {
-                if tile.is_walkable() {
-                    *pos.x_mut() = pos.x() + self.dx.x();
-                    *pos.y_mut() = pos.y() + self.dx.y();
+        let occupants = world.spatial.borrow().at(desired_pos).to_vec();
+        let (hostile, blocked) = {
+            let entities = world.entities.borrow();
+            let occupants = occupants
+                .iter()
+                .filter(|key| **key != self.target)
+                .filter_map(|key| entities.get(*key).map(|entity| (*key, entity)))
+                .collect::<Vec<_>>();
+            let hostile = occupants.iter().find_map(|(key, occupant)| {
+                match (faction, occupant.get::<Faction>().copied()) {
+                    (Some(faction), Some(other)) if faction.is_hostile_to(other) => Some(*key),
+                    _ => None,
                 }
-            }
+            });
+            let blocked = occupants
+                .iter()
+                .any(|(_, occupant)| occupant.has::<Blocking>());
+            (hostile, blocked)
+        };
+
+        if let Some(enemy) = hostile {
+            return ActionResult::Then(vec![Box::new(
+                AttackAction::melee(world, self.target).to(enemy),
+            )]);
+        }
+        if blocked {
+            return ActionResult::Failed;
+        }
+
+        if let Some(pos) = world
+            .entities
+            .borrow_mut()
+            .get_mut(self.target)
+            .unwrap()
+            .get_mut::<Position>()
+        {
+            pos.0 = desired_pos;
+        }
+        world
+            .spatial
+            .borrow_mut()
+            .move_entity(self.target, desired_pos);
+
+        let mut triggers = world.triggers.borrow_mut();
+        let mut actions = triggers.fire(pos, TriggerEvent::Leave, self.target);
+        actions.extend(triggers.fire(desired_pos, TriggerEvent::Enter, self.target));
+        if actions.is_empty() {
+            ActionResult::Done
+        } else {
+            ActionResult::Then(actions)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::property::Property, test_support::TestTile, ActionQueue, Dimension2D, IntExtent2D,
+        Trigger, TriggerEffect,
+    };
+
+    fn spawn(world: &World<TestTile>, pos: IntVector2, properties: Vec<Property>) -> EntityKey {
+        world.spawn("Test", |entity| {
+            entity.add_property(Property::Position(pos));
+            properties
+                .iter()
+                .for_each(|property| entity.add_property(property.clone()));
+        })
+    }
+
+    #[test]
+    fn test_move_collision_and_bump() {
+        let world = World::<TestTile>::new();
+        let mut map = Map::new(IntExtent2D::new(0, 0, 4, 1), Dimension2D::new(1, 1));
+        for x in 0..4 {
+            map.set(x, 0, TestTile::default());
+        }
+        let player = spawn(
+            &world,
+            IntVector2::new(1, 0),
+            vec![Property::Blocking, Property::Faction(Faction::Player)],
+        );
+        spawn(
+            &world,
+            IntVector2::new(0, 0),
+            vec![Property::Blocking, Property::Faction(Faction::Neutral)],
+        );
+        let monster = spawn(
+            &world,
+            IntVector2::new(2, 0),
+            vec![Property::Blocking, Property::Faction(Faction::Monster)],
+        );
+
+        let left = MoveAction::new(IntVector2::new(-1, 0), player);
+        assert!(matches!(
+            left.perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+
+        let right = MoveAction::new(IntVector2::new(1, 0), player);
+        assert!(matches!(
+            right.perform(&world, &mut map),
+            ActionResult::Then(actions) if actions.len() == 1
+        ));
+        assert_eq!(
+            world.spatial.borrow().position(player),
+            Some(IntVector2::new(1, 0))
+        );
+
+        world.despawn(monster);
+        assert!(matches!(
+            right.perform(&world, &mut map),
+            ActionResult::Done
+        ));
+        assert_eq!(world.spatial.borrow().at(IntVector2::new(2, 0)), &[player]);
+    }
+
+    #[test]
+    fn test_move_fires_triggers() {
+        let mut world = World::<TestTile>::new();
+        let mut map = Map::new(IntExtent2D::new(0, 0, 4, 1), Dimension2D::new(1, 1));
+        for x in 0..4 {
+            map.set(x, 0, TestTile::default());
+        }
+        let player = spawn(&world, IntVector2::new(0, 0), vec![]);
+        world.triggers.borrow_mut().add(
+            IntVector2::new(1, 0),
+            Trigger::new(
+                TriggerEvent::Enter,
+                TriggerEffect::Teleport(IntVector2::new(3, 0)),
+            )
+            .hidden(10),
+        );
+
+        let mut queue = ActionQueue::new();
+        queue.add(Box::new(MoveAction::new(IntVector2::new(1, 0), player)));
+        queue.process_actions(&mut world, &mut map);
+        assert_eq!(
+            world.spatial.borrow().position(player),
+            Some(IntVector2::new(3, 0))
+        );
+        assert!(!world.triggers.borrow().at(IntVector2::new(1, 0))[0].is_hidden());
+    }
+}
@@ -0,0 +1,133 @@
+#![allow(dead_code)]
+use crate::{
+    entity::{
+        component::Position,
+        event::WorldEvent,
+        inventory::Inventory,
+        world::{EntityKey, ItemKey, World},
+    },
+    Map, MapCommand, Tile, Vec2,
+};
+
+use super::{Action, ActionResult};
+
+/// Moves an item from the tile the entity stands on into its `Inventory`.
+#[derive(Debug)]
+pub struct PickUpAction<T: Tile> {
+    pub entity: EntityKey,
+    /// The item to take, `None` for the last one put on the tile.
+    pub item: Option<ItemKey>,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Tile> PickUpAction<T> {
+    pub fn new(entity: EntityKey) -> Self {
+        Self {
+            entity,
+            item: None,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    pub fn item(mut self, item: ItemKey) -> Self {
+        self.item = Some(item);
+        self
+    }
+}
+
+impl<T: Tile> Action<T> for PickUpAction<T> {
+    /// Fails if the entity has no position or inventory, the item is not on its tile,
+    /// or the inventory cannot hold it.
+    fn perform(&self, world: &World<T>, map: &mut Map<T>) -> ActionResult<T> {
+        let Some(pos) = world
+            .entities
+            .borrow()
+            .get(self.entity)
+            .and_then(|entity| entity.get::<Position>().map(|pos| pos.0))
+        else {
+            return ActionResult::Failed;
+        };
+        let on_tile = map
+            .get(pos.x(), pos.y())
+            .map(|tile| tile.items().to_vec())
+            .unwrap_or_default();
+        let Some(item) = self.item.or(on_tile.last().copied()) else {
+            return ActionResult::Failed;
+        };
+        if !on_tile.contains(&item) {
+            return ActionResult::Failed;
+        }
+
+        let added = world
+            .entities
+            .borrow_mut()
+            .get_mut(self.entity)
+            .and_then(|entity| entity.get_mut::<Inventory>())
+            .map(|inventory| inventory.add(item, &world.items.borrow()));
+        match added {
+            Some(Ok(())) => {
+                MapCommand::RemoveItem(pos, item).apply(map);
+                world.emit(WorldEvent::PickedUp {
+                    entity: self.entity,
+                    item,
+                });
+                ActionResult::Done
+            }
+            _ => ActionResult::Failed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::item::{ItemBuilder, ItemKind},
+        test_support::ItemTile,
+        Dimension2D, DropAction, IntExtent2D, IntVector2, ItemContainer,
+    };
+
+    #[test]
+    fn test_pick_up_and_drop() {
+        let world = World::<ItemTile>::new();
+        let mut map = Map::new(IntExtent2D::new(0, 0, 1, 1), Dimension2D::new(1, 1));
+        map.set(0, 0, ItemTile::default());
+        let build = |weight| {
+            ItemBuilder::new("Rock".to_owned(), ItemKind::Misc)
+                .with_weight(weight)
+                .build(&world)
+        };
+        let (rock, boulder) = (build(2), build(20));
+        map.add_item(0, 0, rock);
+        map.add_item(0, 0, boulder);
+        let entity = world.spawn("Picker", |entity| {
+            entity.insert(Position(IntVector2::new(0, 0)));
+            entity.insert(Inventory::new(5, 10));
+        });
+
+        // the boulder is on top but too heavy
+        let pick = PickUpAction::new(entity);
+        assert!(matches!(
+            pick.perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+        let pick = PickUpAction::new(entity).item(rock);
+        assert!(matches!(pick.perform(&world, &mut map), ActionResult::Done));
+        assert_eq!(map.get(0, 0).unwrap().items(), &[boulder]);
+
+        let drop = DropAction::new(entity, rock);
+        assert!(matches!(drop.perform(&world, &mut map), ActionResult::Done));
+        assert!(matches!(
+            drop.perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+        assert_eq!(map.get(0, 0).unwrap().items(), &[boulder, rock]);
+        assert_eq!(
+            world.take_events(),
+            vec![
+                WorldEvent::PickedUp { entity, item: rock },
+                WorldEvent::Dropped { entity, item: rock }
+            ]
+        );
+    }
+}
@@ -1,10 +1,10 @@
-use std::fmt::Debug;
+use std::{collections::VecDeque, fmt::Debug};
 
-use crate::{map, world::World, Action, Tile};
+use crate::{map, world::World, Action, ActionResult, Tile};
 
 /// A queue of actions that can be performed on the world and map.
 pub struct ActionQueue<T: Tile> {
-    actions: Vec<Box<dyn Action<T>>>,
+    actions: VecDeque<Box<dyn Action<T>>>,
 }
 
 /// A debug implementation that only shows the number of actions in the queue.
@@ -19,13 +19,13 @@ impl<T: Tile> Debug for ActionQueue<T> {
 impl<T: Tile> ActionQueue<T> {
     pub fn new() -> Self {
         Self {
-            actions: Vec::new(),
+            actions: VecDeque::new(),
         }
     }
 
     /// Adds an action to the queue.
     pub fn add(&mut self, action: Box<dyn Action<T>>) {
-        self.actions.push(action);
+        self.actions.push_back(action);
     }
 
     /// Adds all actions from the given vector to the queue.
@@ -43,11 +43,17 @@ impl<T: Tile> ActionQueue<T> {
     }
 
     /// Process all actions in the queue, updating the world and map accordingly.
+    ///
+    /// Follow-up actions returned through `ActionResult::Then` are performed right after
+    /// the action that produced them, before the rest of the queue.
     pub fn process_actions(&mut self, world: &mut World<T>, map: &mut map::Map<T>) {
-        for action in self.actions.iter() {
-            action.perform(world, map);
+        while let Some(action) = self.actions.pop_front() {
+            if let ActionResult::Then(actions) = action.perform(world, map) {
+                for action in actions.into_iter().rev() {
+                    self.actions.push_front(action);
+                }
+            }
         }
-        self.actions.clear();
     }
 }
 
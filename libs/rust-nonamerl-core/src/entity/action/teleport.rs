@@ -0,0 +1,108 @@
+#![allow(dead_code)]
+use crate::{
+    entity::{
+        component::{Blocking, Position},
+        world::{EntityKey, World},
+    },
+    IntVector2, Map, Tile, Vec2,
+};
+
+use super::{Action, ActionResult};
+
+/// Puts the target entity on another cell without walking there.
+///
+/// Unlike `MoveAction`, arriving does not fire the triggers of the destination, so two
+/// teleporters pointing at each other do not bounce the entity forever.
+#[derive(Debug)]
+pub struct TeleportAction<T: Tile> {
+    pub target: EntityKey,
+    pub to: IntVector2,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Tile> TeleportAction<T> {
+    pub fn new(target: EntityKey, to: IntVector2) -> Self {
+        Self {
+            target,
+            to,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Tile> Action<T> for TeleportAction<T> {
+    /// Fails if the destination is not walkable or another blocking entity stands there.
+    fn perform(&self, world: &World<T>, map: &mut Map<T>) -> ActionResult<T> {
+        if !map.is_walkable(self.to.x(), self.to.y()) {
+            return ActionResult::Failed;
+        }
+        let blocked = {
+            let entities = world.entities.borrow();
+            world
+                .spatial
+                .borrow()
+                .at(self.to)
+                .iter()
+                .filter(|key| **key != self.target)
+                .filter_map(|key| entities.get(*key))
+                .any(|occupant| occupant.has::<Blocking>())
+        };
+        if blocked {
+            return ActionResult::Failed;
+        }
+        match world
+            .entities
+            .borrow_mut()
+            .get_mut(self.target)
+            .and_then(|entity| entity.get_mut::<Position>())
+        {
+            Some(pos) => pos.0 = self.to,
+            None => return ActionResult::Failed,
+        }
+        world.spatial.borrow_mut().move_entity(self.target, self.to);
+        ActionResult::Done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity::property::Property, test_support::TestTile, Dimension2D, IntExtent2D};
+
+    #[test]
+    fn test_teleport_onto_blocking_entity_fails() {
+        let world = World::<TestTile>::new();
+        let mut map = Map::new(IntExtent2D::new(0, 0, 4, 1), Dimension2D::new(1, 1));
+        for x in 0..4 {
+            map.set(x, 0, TestTile::default());
+        }
+        let spawn = |x| {
+            world.spawn("Test", |entity| {
+                entity.add_property(Property::Position(IntVector2::new(x, 0)));
+                entity.add_property(Property::Blocking);
+            })
+        };
+        let player = spawn(0);
+        spawn(3);
+
+        let teleport = TeleportAction::new(player, IntVector2::new(3, 0));
+        assert!(matches!(
+            teleport.perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+        assert_eq!(
+            world.spatial.borrow().position(player),
+            Some(IntVector2::new(0, 0))
+        );
+
+        let teleport = TeleportAction::new(player, IntVector2::new(2, 0));
+        assert!(matches!(
+            teleport.perform(&world, &mut map),
+            ActionResult::Done
+        ));
+        assert_eq!(
+            world.spatial.borrow().position(player),
+            Some(IntVector2::new(2, 0))
+        );
+    }
+}
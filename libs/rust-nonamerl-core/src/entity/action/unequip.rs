@@ -0,0 +1,77 @@
+#![allow(dead_code)]
+use crate::{
+    entity::{
+        component::Position,
+        equipment::{EquipSlot, Equipment},
+        event::WorldEvent,
+        inventory::Inventory,
+        world::{EntityKey, ItemKey, World},
+    },
+    Map, MapCommand, Tile,
+};
+
+use super::{Action, ActionResult};
+
+/// Takes off the item in one of the entity's equipment slots.
+///
+/// The item stays in the entity's `Inventory`, or is dropped on its cell if it has none.
+#[derive(Debug, Clone, Copy)]
+pub struct UnequipAction<T: Tile> {
+    pub entity: EntityKey,
+    pub slot: EquipSlot,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Tile> UnequipAction<T> {
+    pub fn new(entity: EntityKey, slot: EquipSlot) -> Self {
+        Self {
+            entity,
+            slot,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Tile> Action<T> for UnequipAction<T> {
+    /// Fails if the slot is empty.
+    fn perform(&self, world: &World<T>, map: &mut Map<T>) -> ActionResult<T> {
+        let item = world
+            .entities
+            .borrow_mut()
+            .get_mut(self.entity)
+            .and_then(|entity| entity.get_mut::<Equipment>())
+            .and_then(|equipment| equipment.unequip(self.slot));
+        match item {
+            Some(item) => {
+                put_away(world, map, self.entity, item);
+                ActionResult::Done
+            }
+            None => ActionResult::Failed,
+        }
+    }
+}
+
+/// Emits `Unequipped` for an item just taken off, and drops it on the entity's cell unless
+/// the entity carries it.
+pub(super) fn put_away<T: Tile>(
+    world: &World<T>,
+    map: &mut Map<T>,
+    entity: EntityKey,
+    item: ItemKey,
+) {
+    let pos = {
+        let entities = world.entities.borrow();
+        let owner = entities.get(entity);
+        let carried = owner
+            .and_then(|owner| owner.get::<Inventory>())
+            .is_some_and(|inventory| inventory.contains(item));
+        owner
+            .and_then(|owner| owner.get::<Position>())
+            .filter(|_| !carried)
+            .map(|pos| pos.0)
+    };
+    if let Some(pos) = pos {
+        MapCommand::AddItem(pos, item).apply(map);
+    }
+    world.emit(WorldEvent::Unequipped { entity, item });
+}
@@ -0,0 +1,206 @@
+#![allow(dead_code)]
+use crate::{
+    entity::{
+        activator::UseMode,
+        equipment::Equipment,
+        event::WorldEvent,
+        inventory::Inventory,
+        world::{EntityKey, ItemKey, World},
+    },
+    Map, Tile,
+};
+
+use super::{Action, ActionResult};
+
+/// Uses an item through its activator for `mode`, e.g. drinks a potion.
+///
+/// The action built by the activator is queued as a follow-up. Once it succeeds, single-use
+/// items (see `Item::is_single_use`) are taken out of the user's inventory and removed from
+/// the world. If the user has an `Inventory`, the item must be in it.
+#[derive(Debug)]
+pub struct UseItemAction<T: Tile> {
+    pub item: ItemKey,
+    pub user: EntityKey,
+    pub mode: UseMode,
+    /// The entity the item is used on, required by `UseMode::Attack`.
+    pub target: Option<EntityKey>,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Tile> UseItemAction<T> {
+    pub fn new(item: ItemKey, user: EntityKey, mode: UseMode) -> Self {
+        Self {
+            item,
+            user,
+            mode,
+            target: None,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    pub fn on(mut self, target: EntityKey) -> Self {
+        self.target = Some(target);
+        self
+    }
+}
+
+impl<T: Tile> Action<T> for UseItemAction<T> {
+    /// Fails if the item has no activator for `mode` or the activator returns no action.
+    fn perform(&self, world: &World<T>, _map: &mut Map<T>) -> ActionResult<T> {
+        let usable = world.entities.borrow().get(self.user).is_some_and(|user| {
+            user.get::<Inventory>()
+                .is_none_or(|inventory| inventory.contains(self.item))
+        });
+        if !usable {
+            return ActionResult::Failed;
+        }
+        let Some((activator, single_use)) = world.items.borrow().get(self.item).and_then(|item| {
+            item.activator(self.mode)
+                .map(|activator| (activator.clone(), item.is_single_use()))
+        }) else {
+            return ActionResult::Failed;
+        };
+        let Some(action) = activator.activate(self.item, self.user, world, self.target) else {
+            return ActionResult::Failed;
+        };
+        ActionResult::Then(vec![Box::new(ConsumeOnSuccess {
+            action,
+            item: self.item,
+            user: self.user,
+            single_use,
+        })])
+    }
+}
+
+/// Performs the action of an activator, then consumes the single-use item it came from
+/// unless the action failed. The queue does not tell a follow-up whether the action before
+/// it succeeded, so both happen in one step.
+struct ConsumeOnSuccess<T: Tile> {
+    action: Box<dyn Action<T>>,
+    item: ItemKey,
+    user: EntityKey,
+    single_use: bool,
+}
+
+impl<T: Tile> Action<T> for ConsumeOnSuccess<T> {
+    fn perform(&self, world: &World<T>, map: &mut Map<T>) -> ActionResult<T> {
+        let result = self.action.perform(world, map);
+        if !self.single_use || matches!(result, ActionResult::Failed) {
+            return result;
+        }
+        if let Some(user) = world.entities.borrow_mut().get_mut(self.user) {
+            if let Some(inventory) = user.get_mut::<Inventory>() {
+                inventory.remove(self.item);
+            }
+            if let Some(equipment) = user.get_mut::<Equipment>() {
+                equipment.remove(self.item);
+            }
+        }
+        world.items.borrow_mut().remove(self.item);
+        world.emit(WorldEvent::Consumed {
+            entity: self.user,
+            item: self.item,
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::{
+            activator::{drink_potion, UseKind},
+            component::Health,
+            item::{ItemBuilder, ItemKind, Potion},
+            property::HealthData,
+        },
+        test_support::TestTile,
+        ActionQueue, AttackAction, Dimension2D, IntExtent2D,
+    };
+
+    fn stab(
+        _item: ItemKey,
+        user: EntityKey,
+        _world: &World<TestTile>,
+        target: EntityKey,
+    ) -> Option<Box<dyn Action<TestTile>>> {
+        Some(Box::new(AttackAction::new(2).from(user).to(target)))
+    }
+
+    #[test]
+    fn test_use_items() {
+        let mut world = World::<TestTile>::new();
+        let mut map = Map::new(IntExtent2D::new(0, 0, 1, 1), Dimension2D::new(1, 1));
+        let potion = ItemKind::Potion(Potion {
+            health: HealthData { health: 5 },
+            effect: None,
+        });
+        let potion = ItemBuilder::new("Potion".to_owned(), potion)
+            .add_activator(UseKind::Drink {
+                activate_fn: drink_potion,
+            })
+            .build(&world);
+        let knife = ItemBuilder::new("Knife".to_owned(), ItemKind::Misc)
+            .add_activator(UseKind::Attack { activate_fn: stab })
+            .build(&world);
+        let player = world.spawn("Player", |entity| {
+            entity.insert(Health { current: 3, max: 6 });
+            let mut inventory = Inventory::new(5, 10);
+            inventory.add(potion, &world.items.borrow()).unwrap();
+            inventory.add(knife, &world.items.borrow()).unwrap();
+            entity.insert(inventory);
+        });
+        let rat = world.spawn("Rat", |entity| {
+            entity.insert(Health::new(4));
+        });
+        // nothing to heal, so the potion is kept
+        let ghost = world.spawn("Ghost", |_| {});
+
+        assert!(matches!(
+            UseItemAction::new(potion, player, UseMode::Attack).perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+        assert!(matches!(
+            UseItemAction::new(knife, player, UseMode::Attack).perform(&world, &mut map),
+            ActionResult::Failed
+        ));
+        let mut queue = ActionQueue::new();
+        queue.add(Box::new(UseItemAction::new(potion, ghost, UseMode::Drink)));
+        queue.process_actions(&mut world, &mut map);
+        assert!(world.items.borrow().get(potion).is_some());
+        assert!(world.take_events().is_empty());
+
+        queue.add(Box::new(UseItemAction::new(potion, player, UseMode::Drink)));
+        queue.add(Box::new(
+            UseItemAction::new(knife, player, UseMode::Attack).on(rat),
+        ));
+        queue.process_actions(&mut world, &mut map);
+
+        let entities = world.entities.borrow();
+        let user = entities.get(player).unwrap();
+        assert_eq!(user.get::<Health>(), Some(&Health::new(6)));
+        assert_eq!(
+            entities.get(rat).unwrap().get::<Health>().unwrap().current,
+            2
+        );
+        assert_eq!(
+            user.get::<Inventory>().unwrap().items().collect::<Vec<_>>(),
+            vec![knife]
+        );
+        assert!(world.items.borrow().get(potion).is_none());
+        assert_eq!(
+            world.take_events()[..2],
+            [
+                WorldEvent::Healed {
+                    entity: player,
+                    amount: 3
+                },
+                WorldEvent::Consumed {
+                    entity: player,
+                    item: potion
+                },
+            ]
+        );
+    }
+}
@@ -2,7 +2,8 @@
 use crate::Tile;
 
 use super::{
-    action::Action,
+    action::{Action, ApplyStatusAction, HealAction},
+    item::{ItemKind, Potion},
     world::{EntityKey, ItemKey, World},
 };
 
@@ -43,6 +44,59 @@ pub enum UseKind<T: Tile> {
     Drink { activate_fn: DrinkActivateFn<T> },
 }
 
+/// The variants of `UseKind`, without their activators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UseMode {
+    Attack,
+    Defense,
+    Pick,
+    Drink,
+}
+
+impl<T: Tile> UseKind<T> {
+    pub fn mode(&self) -> UseMode {
+        match self {
+            UseKind::Attack { .. } => UseMode::Attack,
+            UseKind::Defense { .. } => UseMode::Defense,
+            UseKind::Pick { .. } => UseMode::Pick,
+            UseKind::Drink { .. } => UseMode::Drink,
+        }
+    }
+
+    /// Calls the activator. `Attack` activators need a `target` and return `None` without one.
+    pub fn activate(
+        &self,
+        item: ItemKey,
+        user: EntityKey,
+        world: &World<T>,
+        target: Option<EntityKey>,
+    ) -> Option<Box<dyn Action<T>>> {
+        match self {
+            UseKind::Attack { activate_fn } => activate_fn(item, user, world, target?),
+            UseKind::Defense { activate_fn } => activate_fn(item, user, world),
+            UseKind::Pick { activate_fn } => activate_fn(item, user, world),
+            UseKind::Drink { activate_fn } => activate_fn(item, user, world),
+        }
+    }
+}
+
+/// The `Drink` activator of potions: applies the potion's effect to the drinker, or heals
+/// it by the potion's health.
+pub fn drink_potion<T: Tile>(
+    item: ItemKey,
+    user: EntityKey,
+    world: &World<T>,
+) -> Option<Box<dyn Action<T>>> {
+    match world.items.borrow().get(item)?.class {
+        ItemKind::Potion(Potion {
+            effect: Some(effect),
+            ..
+        }) => Some(Box::new(ApplyStatusAction::new(user, effect))),
+        ItemKind::Potion(potion) => Some(Box::new(HealAction::new(user, potion.health.health))),
+        _ => None,
+    }
+}
+
 // impl UseKind {
 //     pub fn can_activate(
 //         &self,
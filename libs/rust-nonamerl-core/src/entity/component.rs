@@ -0,0 +1,298 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::Debug,
+};
+
+use serde::Deserialize;
+
+use crate::IntVector2;
+
+/// A piece of data attached to an entity, e.g. its position or its health.
+///
+/// Each component type is stored once per entity and is reachable both by type,
+/// with `Entity::get::<C>()`, and by its `NAME`, for data-driven code.
+pub trait Component: Any + Debug + Clone {
+    const NAME: &'static str;
+}
+
+/// The object-safe side of `Component`, used to store components of different types together.
+pub trait AnyComponent: Any + Debug {
+    fn name(&self) -> &'static str;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn clone_box(&self) -> Box<dyn AnyComponent>;
+}
+
+impl<C: Component> AnyComponent for C {
+    fn name(&self) -> &'static str {
+        C::NAME
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn AnyComponent> {
+        Box::new(self.clone())
+    }
+}
+
+impl dyn AnyComponent {
+    pub fn downcast_ref<C: Component>(&self) -> Option<&C> {
+        self.as_any().downcast_ref()
+    }
+
+    pub fn downcast_mut<C: Component>(&mut self) -> Option<&mut C> {
+        self.as_any_mut().downcast_mut()
+    }
+}
+
+impl Clone for Box<dyn AnyComponent> {
+    fn clone(&self) -> Self {
+        self.as_ref().clone_box()
+    }
+}
+
+/// The components of one entity, one slot per component type.
+#[derive(Debug, Clone, Default)]
+pub struct Components {
+    components: HashMap<TypeId, Box<dyn AnyComponent>>,
+    names: HashMap<&'static str, TypeId>,
+}
+
+impl Components {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `component`, returning the one of the same type it replaced.
+    pub fn insert<C: Component>(&mut self, component: C) -> Option<C> {
+        self.insert_boxed(Box::new(component))
+            .and_then(|previous| previous.as_any().downcast_ref::<C>().cloned())
+    }
+
+    /// Adds a component whose type is only known at runtime.
+    pub fn insert_boxed(
+        &mut self,
+        component: Box<dyn AnyComponent>,
+    ) -> Option<Box<dyn AnyComponent>> {
+        let type_id = component.as_any().type_id();
+        self.names.insert(component.name(), type_id);
+        self.components.insert(type_id, component)
+    }
+
+    pub fn get<C: Component>(&self) -> Option<&C> {
+        self.components
+            .get(&TypeId::of::<C>())
+            .and_then(|component| component.downcast_ref())
+    }
+
+    pub fn get_mut<C: Component>(&mut self) -> Option<&mut C> {
+        self.components
+            .get_mut(&TypeId::of::<C>())
+            .and_then(|component| component.downcast_mut())
+    }
+
+    pub fn remove<C: Component>(&mut self) -> Option<C> {
+        self.remove_by_name(C::NAME)
+            .and_then(|component| component.downcast_ref::<C>().cloned())
+    }
+
+    pub fn has<C: Component>(&self) -> bool {
+        self.components.contains_key(&TypeId::of::<C>())
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&dyn AnyComponent> {
+        let type_id = self.names.get(name)?;
+        self.components
+            .get(type_id)
+            .map(|component| component.as_ref())
+    }
+
+    pub fn get_by_name_mut(&mut self, name: &str) -> Option<&mut dyn AnyComponent> {
+        let type_id = self.names.get(name)?;
+        self.components
+            .get_mut(type_id)
+            .map(|component| component.as_mut())
+    }
+
+    pub fn remove_by_name(&mut self, name: &str) -> Option<Box<dyn AnyComponent>> {
+        let type_id = self.names.remove(name)?;
+        self.components.remove(&type_id)
+    }
+
+    pub fn has_name(&self, name: &str) -> bool {
+        self.names.contains_key(name)
+    }
+
+    /// The names of the components, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.names.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position(pub IntVector2);
+
+impl Component for Position {
+    const NAME: &'static str = "position";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Health {
+    pub current: i32,
+    pub max: i32,
+}
+
+impl Health {
+    /// Full health.
+    pub fn new(max: i32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0
+    }
+}
+
+impl Component for Health {
+    const NAME: &'static str = "health";
+}
+
+/// Experience collected by the entity, see `World::award_xp`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xp(pub i32);
+
+impl Component for Xp {
+    const NAME: &'static str = "xp";
+}
+
+/// Experience awarded to whoever kills the entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XpReward(pub i32);
+
+impl Component for XpReward {
+    const NAME: &'static str = "xp_reward";
+}
+
+/// A display name, when it differs from the name the entity was spawned with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Name(pub String);
+
+impl Component for Name {
+    const NAME: &'static str = "name";
+}
+
+/// The name of the item left behind when the entity dies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Corpse(pub String);
+
+impl Component for Corpse {
+    const NAME: &'static str = "corpse";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gold(pub i32);
+
+impl Component for Gold {
+    const NAME: &'static str = "gold";
+}
+
+/// How well the entity spots hidden traps, see `Triggers::search`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Perception(pub u32);
+
+impl Component for Perception {
+    const NAME: &'static str = "perception";
+}
+
+/// Energy spent by one action. An entity gains as much every turn at normal speed.
+pub const ACTION_COST: i32 = 10;
+
+/// Energy stored by the entity to act, see `World::gain_energy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Energy(pub i32);
+
+impl Component for Energy {
+    const NAME: &'static str = "energy";
+}
+
+/// The entity occupies its cell, so other entities cannot move into it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blocking;
+
+impl Component for Blocking {
+    const NAME: &'static str = "blocking";
+}
+
+/// Which side an entity fights for. Entities of hostile factions attack each other on bump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Faction {
+    Player,
+    Monster,
+    Neutral,
+}
+
+impl Faction {
+    pub fn is_hostile_to(&self, other: Faction) -> bool {
+        matches!(
+            (self, other),
+            (Faction::Player, Faction::Monster) | (Faction::Monster, Faction::Player)
+        )
+    }
+}
+
+impl Component for Faction {
+    const NAME: &'static str = "faction";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_and_named_access() {
+        let mut components = Components::new();
+        components.insert(Position(IntVector2::new(1, 2)));
+        components.insert(Health::new(10));
+        assert_eq!(components.insert(Xp(1)), None);
+        assert_eq!(components.insert(Xp(5)), Some(Xp(1)));
+
+        components.get_mut::<Health>().unwrap().current -= 3;
+        assert_eq!(
+            components.get::<Health>(),
+            Some(&Health {
+                current: 7,
+                max: 10
+            })
+        );
+        assert!(!components.has::<Gold>());
+
+        let position = components.get_by_name("position").unwrap();
+        assert_eq!(position.name(), Position::NAME);
+        assert_eq!(
+            position.downcast_ref::<Position>(),
+            Some(&Position(IntVector2::new(1, 2)))
+        );
+        assert!(position.downcast_ref::<Xp>().is_none());
+
+        assert_eq!(components.remove::<Xp>(), Some(Xp(5)));
+        assert!(!components.has_name(Xp::NAME));
+        assert!(components.remove_by_name("health").is_some());
+        assert_eq!(components.len(), 1);
+    }
+}
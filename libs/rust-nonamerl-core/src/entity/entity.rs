@@ -1,7 +1,6 @@
 #![allow(dead_code)]
-use std::collections::HashMap;
-
 use super::{
+    component::{AnyComponent, Component, Components},
     property::Property,
     world::{EntityKey, World},
     WithId,
@@ -14,14 +13,11 @@ use super::{
 //     properties: HashMap<&'static str, Property>,
 // }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Entity {
     id: EntityKey,
     name: String,
-    health: i32,
-    // xp: i32,
-    // inventory: Option<Inventory>,
-    properties: HashMap<&'static str, Property>,
+    components: Components,
 }
 
 impl Entity {
@@ -29,25 +25,54 @@ impl Entity {
         Self {
             id,
             name: name.to_string(),
-            health: 100,
-            properties: HashMap::new(),
+            components: Components::new(),
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get<C: Component>(&self) -> Option<&C> {
+        self.components.get()
+    }
+
+    pub fn get_mut<C: Component>(&mut self) -> Option<&mut C> {
+        self.components.get_mut()
+    }
+
+    /// Adds a component, returning the one of the same type it replaced.
+    pub fn insert<C: Component>(&mut self, component: C) -> Option<C> {
+        self.components.insert(component)
+    }
+
+    pub fn remove<C: Component>(&mut self) -> Option<C> {
+        self.components.remove()
+    }
+
+    pub fn has<C: Component>(&self) -> bool {
+        self.components.has::<C>()
+    }
+
     pub fn has_property(&self, property_name: &str) -> bool {
-        self.properties.contains_key(property_name)
+        self.components.has_name(property_name)
     }
 
-    pub fn get_property(&self, property_name: &str) -> Option<&Property> {
-        self.properties.get(property_name)
+    /// Looks a component up by its `Component::NAME`.
+    pub fn get_property(&self, property_name: &str) -> Option<&dyn AnyComponent> {
+        self.components.get_by_name(property_name)
     }
 
-    pub fn get_property_mut(&mut self, property_name: &str) -> Option<&mut Property> {
-        self.properties.get_mut(property_name)
+    pub fn get_property_mut(&mut self, property_name: &str) -> Option<&mut dyn AnyComponent> {
+        self.components.get_by_name_mut(property_name)
     }
 
     pub fn add_property(&mut self, property: Property) {
-        self.properties.insert(property.name(), property);
+        self.components.insert_boxed(property.into_component());
+    }
+
+    pub fn components(&self) -> &Components {
+        &self.components
     }
 }
 
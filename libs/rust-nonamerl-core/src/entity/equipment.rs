@@ -0,0 +1,268 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use serde::Deserialize;
+
+use crate::Tile;
+
+use super::{
+    component::Component,
+    item::{Item, ItemKind},
+    world::{ItemKey, SlotMapStorage},
+};
+
+/// Where an item is worn or held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EquipSlot {
+    MainHand,
+    OffHand,
+    Head,
+    Body,
+    Hands,
+    Feet,
+    Neck,
+    Ring,
+}
+
+impl EquipSlot {
+    pub fn is_hand(&self) -> bool {
+        matches!(self, EquipSlot::MainHand | EquipSlot::OffHand)
+    }
+}
+
+/// The slots `item` can be equipped in, best first. Empty if it cannot be equipped.
+///
+/// Weapons go in either hand, two-handed ones only in the main hand (they take the
+/// off hand too). Armour goes in its own slot.
+pub fn slots_for<T: Tile>(item: &Item<T>) -> Vec<EquipSlot> {
+    match &item.class {
+        ItemKind::Weapon(_) if item.is_two_handed() => vec![EquipSlot::MainHand],
+        ItemKind::Weapon(_) => vec![EquipSlot::MainHand, EquipSlot::OffHand],
+        ItemKind::Armor(armor) => vec![armor.slot],
+        _ => vec![],
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EquipError {
+    NotEquipable,
+    /// The item does not fit the requested slot.
+    WrongSlot(EquipSlot),
+    AlreadyEquipped,
+    UnknownItem,
+}
+
+impl Display for EquipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EquipError::NotEquipable => write!(f, "item cannot be equipped"),
+            EquipError::WrongSlot(slot) => write!(f, "item does not fit the {:?} slot", slot),
+            EquipError::AlreadyEquipped => write!(f, "item already equipped"),
+            EquipError::UnknownItem => write!(f, "unknown item"),
+        }
+    }
+}
+
+impl std::error::Error for EquipError {}
+
+/// Stats granted by everything an entity has equipped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EquipmentBonus {
+    pub damage: i32,
+    pub defense: i32,
+}
+
+/// The items an entity wears or holds, one per `EquipSlot`.
+///
+/// A two-handed weapon fills both `MainHand` and `OffHand`. Equipped items are not taken
+/// out of the `Inventory`: an entity that has one must carry what it equips.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Equipment {
+    slots: BTreeMap<EquipSlot, ItemKey>,
+}
+
+impl Component for Equipment {
+    const NAME: &'static str = "equipment";
+}
+
+impl Equipment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, slot: EquipSlot) -> Option<ItemKey> {
+        self.slots.get(&slot).copied()
+    }
+
+    /// The first slot holding `item`.
+    pub fn slot_of(&self, item: ItemKey) -> Option<EquipSlot> {
+        self.iter().find(|(_, i)| *i == item).map(|(slot, _)| slot)
+    }
+
+    pub fn contains(&self, item: ItemKey) -> bool {
+        self.slot_of(item).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EquipSlot, ItemKey)> + '_ {
+        self.slots.iter().map(|(slot, item)| (*slot, *item))
+    }
+
+    /// Every equipped item, once even if it fills two slots.
+    pub fn items(&self) -> Vec<ItemKey> {
+        let mut items: Vec<ItemKey> = self.slots.values().copied().collect();
+        items.dedup();
+        items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Puts `item` in `slot`, or in its first free slot when `slot` is `None`.
+    ///
+    /// Returns the items taken off to make room.
+    pub fn equip<T: Tile>(
+        &mut self,
+        item: ItemKey,
+        slot: Option<EquipSlot>,
+        items: &SlotMapStorage<ItemKey, Item<T>>,
+    ) -> Result<Vec<ItemKey>, EquipError> {
+        if self.contains(item) {
+            return Err(EquipError::AlreadyEquipped);
+        }
+        let equipped = items.get(item).ok_or(EquipError::UnknownItem)?;
+        let allowed = slots_for(equipped);
+        let slot = match slot {
+            Some(slot) if allowed.contains(&slot) => slot,
+            Some(slot) => return Err(EquipError::WrongSlot(slot)),
+            None => *allowed
+                .iter()
+                .find(|slot| !self.slots.contains_key(slot))
+                .or(allowed.first())
+                .ok_or(EquipError::NotEquipable)?,
+        };
+        let filled = if equipped.is_two_handed() {
+            vec![EquipSlot::MainHand, EquipSlot::OffHand]
+        } else {
+            vec![slot]
+        };
+
+        let removed = filled
+            .iter()
+            .filter_map(|slot| self.unequip(*slot))
+            .collect();
+        for slot in filled {
+            self.slots.insert(slot, item);
+        }
+        Ok(removed)
+    }
+
+    /// Takes off the item in `slot`, emptying every slot it fills.
+    pub fn unequip(&mut self, slot: EquipSlot) -> Option<ItemKey> {
+        let item = self.slots.remove(&slot)?;
+        self.slots.retain(|_, i| *i != item);
+        Some(item)
+    }
+
+    /// Takes off `item`. Returns `false` if it was not equipped.
+    pub fn remove(&mut self, item: ItemKey) -> bool {
+        self.slot_of(item)
+            .and_then(|slot| self.unequip(slot))
+            .is_some()
+    }
+
+    /// The damage and defense of all equipped items added together.
+    pub fn bonus<T: Tile>(&self, items: &SlotMapStorage<ItemKey, Item<T>>) -> EquipmentBonus {
+        self.items()
+            .into_iter()
+            .filter_map(|item| items.get(item))
+            .fold(EquipmentBonus::default(), |bonus, item| {
+                let (damage, defense) = match &item.class {
+                    ItemKind::Weapon(weapon) => (weapon.damage, weapon.defense),
+                    ItemKind::Armor(armor) => (None, armor.defense),
+                    _ => (None, None),
+                };
+                EquipmentBonus {
+                    damage: bonus.damage + damage.map_or(0, |d| d.value),
+                    defense: bonus.defense + defense.map_or(0, |d| d.value),
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::item::{Armor, Damage, Defense, ItemBehaviour, ItemBuilder, Weapon},
+        test_support::TestTile,
+        world::World,
+    };
+
+    fn weapon(world: &World<TestTile>, damage: i32, two_handed: bool) -> ItemKey {
+        let kind = ItemKind::Weapon(Weapon {
+            damage: Some(Damage { value: damage }),
+            defense: None,
+        });
+        let builder = ItemBuilder::new("Weapon".to_owned(), kind);
+        if two_handed {
+            builder.add_behaviour(ItemBehaviour::TwoHanded).build(world)
+        } else {
+            builder.build(world)
+        }
+    }
+
+    fn armor(world: &World<TestTile>, slot: EquipSlot, defense: i32) -> ItemKey {
+        let kind = ItemKind::Armor(Armor {
+            slot,
+            defense: Some(Defense { value: defense }),
+        });
+        ItemBuilder::new("Armor".to_owned(), kind).build(world)
+    }
+
+    #[test]
+    fn test_slots_and_bonus() {
+        let world = World::<TestTile>::new();
+        let dagger = weapon(&world, 2, false);
+        let sword = weapon(&world, 5, false);
+        let axe = weapon(&world, 9, true);
+        let helmet = armor(&world, EquipSlot::Head, 1);
+        let shield = armor(&world, EquipSlot::OffHand, 3);
+        let rock = ItemBuilder::new("Rock".to_owned(), ItemKind::Misc).build(&world);
+        let items = world.items.borrow();
+
+        let mut equipment = Equipment::new();
+        assert_eq!(
+            equipment.equip(rock, None, &items),
+            Err(EquipError::NotEquipable)
+        );
+        assert_eq!(
+            equipment.equip(helmet, Some(EquipSlot::Body), &items),
+            Err(EquipError::WrongSlot(EquipSlot::Body))
+        );
+        assert_eq!(equipment.equip(dagger, None, &items), Ok(vec![]));
+        assert_eq!(equipment.equip(sword, None, &items), Ok(vec![]));
+        assert_eq!(equipment.get(EquipSlot::OffHand), Some(sword));
+        equipment.equip(helmet, None, &items).unwrap();
+        assert_eq!(
+            equipment.bonus(&items),
+            EquipmentBonus {
+                damage: 7,
+                defense: 1
+            }
+        );
+
+        // a two-handed weapon frees both hands
+        assert_eq!(equipment.equip(axe, None, &items), Ok(vec![dagger, sword]));
+        assert_eq!(equipment.items(), vec![axe, helmet]);
+        assert_eq!(equipment.equip(shield, None, &items), Ok(vec![axe]));
+        assert_eq!(equipment.get(EquipSlot::MainHand), None);
+
+        assert!(equipment.remove(helmet));
+        assert_eq!(equipment.unequip(EquipSlot::OffHand), Some(shield));
+        assert!(equipment.is_empty());
+    }
+}
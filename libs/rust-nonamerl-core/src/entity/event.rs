@@ -0,0 +1,80 @@
+use crate::IntVector2;
+
+use super::{
+    status::StatusKind,
+    world::{EntityKey, ItemKey},
+};
+
+/// Something that happened to the entities of a `World`, collected with `World::take_events`.
+///
+/// `attacker` is `None` when the damage comes from the environment, e.g. a trap or a fire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorldEvent {
+    Hit {
+        attacker: Option<EntityKey>,
+        target: EntityKey,
+        /// Damage dealt after the target's defense.
+        damage: i32,
+        /// Damage absorbed by the target's defense.
+        blocked: i32,
+    },
+    /// Damage that no defense can block, e.g. poison or fire, see `DamageAction`.
+    Hurt {
+        entity: EntityKey,
+        damage: i32,
+    },
+    Missed {
+        attacker: Option<EntityKey>,
+        target: EntityKey,
+    },
+    /// The target's health reached zero.
+    Killed {
+        attacker: Option<EntityKey>,
+        target: EntityKey,
+    },
+    PickedUp {
+        entity: EntityKey,
+        item: ItemKey,
+    },
+    Dropped {
+        entity: EntityKey,
+        item: ItemKey,
+    },
+    Equipped {
+        entity: EntityKey,
+        item: ItemKey,
+    },
+    Unequipped {
+        entity: EntityKey,
+        item: ItemKey,
+    },
+    Healed {
+        entity: EntityKey,
+        amount: i32,
+    },
+    /// A single-use item was used up and removed from the world.
+    Consumed {
+        entity: EntityKey,
+        item: ItemKey,
+    },
+    StatusApplied {
+        entity: EntityKey,
+        kind: StatusKind,
+    },
+    StatusExpired {
+        entity: EntityKey,
+        kind: StatusKind,
+    },
+    LevelUp {
+        entity: EntityKey,
+        level: u32,
+    },
+    /// The entity was removed from the world after dying, `entity` is no longer valid
+    /// unless it is the player, see `DeathAction`.
+    Died {
+        entity: EntityKey,
+        name: String,
+        killer: Option<EntityKey>,
+        position: Option<IntVector2>,
+    },
+}
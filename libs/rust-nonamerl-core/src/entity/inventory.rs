@@ -0,0 +1,198 @@
+use std::fmt::{self, Display};
+
+use crate::Tile;
+
+use super::{
+    component::Component,
+    item::Item,
+    world::{ItemKey, SlotMapStorage},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryError {
+    /// Every slot is taken and the item does not stack with anything carried.
+    Full,
+    TooHeavy,
+    UnknownItem,
+    AlreadyCarried,
+}
+
+impl Display for InventoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InventoryError::Full => write!(f, "inventory full"),
+            InventoryError::TooHeavy => write!(f, "too heavy to carry"),
+            InventoryError::UnknownItem => write!(f, "unknown item"),
+            InventoryError::AlreadyCarried => write!(f, "item already carried"),
+        }
+    }
+}
+
+impl std::error::Error for InventoryError {}
+
+/// The items carried by an entity.
+///
+/// Identical items (see `Item::stacks_with`) share a slot, so `capacity` limits the
+/// kinds of items carried while `max_weight` limits their total weight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    stacks: Vec<Vec<ItemKey>>,
+    pub capacity: usize,
+    pub max_weight: u32,
+}
+
+impl Component for Inventory {
+    const NAME: &'static str = "inventory";
+}
+
+impl Inventory {
+    pub fn new(capacity: usize, max_weight: u32) -> Self {
+        Self {
+            stacks: Vec::new(),
+            capacity,
+            max_weight,
+        }
+    }
+
+    /// The carried items, one `Vec` per slot.
+    pub fn stacks(&self) -> &[Vec<ItemKey>] {
+        &self.stacks
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = ItemKey> + '_ {
+        self.stacks.iter().flatten().copied()
+    }
+
+    pub fn contains(&self, item: ItemKey) -> bool {
+        self.items().any(|i| i == item)
+    }
+
+    /// Number of items carried, counting each item of a stack.
+    pub fn len(&self) -> usize {
+        self.stacks.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+
+    pub fn slots_used(&self) -> usize {
+        self.stacks.len()
+    }
+
+    pub fn weight<T: Tile>(&self, items: &SlotMapStorage<ItemKey, Item<T>>) -> u32 {
+        self.items()
+            .filter_map(|item| items.get(item))
+            .map(|item| item.weight)
+            .sum()
+    }
+
+    /// Puts `item` in the stack of identical items, or in a free slot.
+    pub fn add<T: Tile>(
+        &mut self,
+        item: ItemKey,
+        items: &SlotMapStorage<ItemKey, Item<T>>,
+    ) -> Result<(), InventoryError> {
+        if self.contains(item) {
+            return Err(InventoryError::AlreadyCarried);
+        }
+        let added = items.get(item).ok_or(InventoryError::UnknownItem)?;
+        if self.weight(items) + added.weight > self.max_weight {
+            return Err(InventoryError::TooHeavy);
+        }
+        let stack = self.stacks.iter().position(|stack| {
+            items
+                .get(stack[0])
+                .is_some_and(|carried| carried.stacks_with(added))
+        });
+        match stack {
+            Some(index) => self.stacks[index].push(item),
+            None if self.stacks.len() < self.capacity => self.stacks.push(vec![item]),
+            None => return Err(InventoryError::Full),
+        }
+        Ok(())
+    }
+
+    /// Takes `item` out of the inventory. Returns `false` if it was not carried.
+    pub fn remove(&mut self, item: ItemKey) -> bool {
+        let Some(index) = self.stacks.iter().position(|stack| stack.contains(&item)) else {
+            return false;
+        };
+        self.stacks[index].retain(|i| *i != item);
+        if self.stacks[index].is_empty() {
+            self.stacks.remove(index);
+        }
+        true
+    }
+
+    /// Empties the inventory, returning every item it held.
+    pub fn take_all(&mut self) -> Vec<ItemKey> {
+        self.stacks.drain(..).flatten().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::item::{ItemBehaviour, ItemBuilder, ItemKind},
+        test_support::TestTile,
+        world::World,
+    };
+
+    fn item(world: &World<TestTile>, name: &str, weight: u32) -> ItemKey {
+        ItemBuilder::new(name.to_owned(), ItemKind::Misc)
+            .with_weight(weight)
+            .build(world)
+    }
+
+    #[test]
+    fn test_stacking_and_limits() {
+        let world = World::<TestTile>::new();
+        let arrows = [item(&world, "arrow", 1), item(&world, "arrow", 1)];
+        let stone = item(&world, "stone", 3);
+        let anvil = item(&world, "anvil", 50);
+        let pebble = item(&world, "pebble", 1);
+        let items = world.items.borrow();
+
+        let mut inventory = Inventory::new(2, 10);
+        inventory.add(arrows[0], &items).unwrap();
+        inventory.add(arrows[1], &items).unwrap();
+        assert_eq!(inventory.slots_used(), 1);
+        assert_eq!(
+            inventory.add(arrows[1], &items),
+            Err(InventoryError::AlreadyCarried)
+        );
+        assert_eq!(inventory.add(anvil, &items), Err(InventoryError::TooHeavy));
+
+        inventory.add(stone, &items).unwrap();
+        assert_eq!(inventory.weight(&items), 5);
+        assert_eq!(inventory.add(pebble, &items), Err(InventoryError::Full));
+
+        assert!(inventory.remove(arrows[0]));
+        assert!(!inventory.remove(arrows[0]));
+        assert_eq!(inventory.len(), 2);
+        assert_eq!(inventory.take_all(), vec![arrows[1], stone]);
+        assert!(inventory.is_empty());
+    }
+
+    #[test]
+    fn test_only_identical_items_stack() {
+        let world = World::<TestTile>::new();
+        let arrow = item(&world, "arrow", 1);
+        let cake = ItemBuilder::new("arrow".to_owned(), ItemKind::Food)
+            .with_weight(1)
+            .build(&world);
+        let dart = ItemBuilder::new("arrow".to_owned(), ItemKind::Misc)
+            .with_weight(1)
+            .add_behaviour(ItemBehaviour::Throwable)
+            .build(&world);
+        let items = world.items.borrow();
+
+        let mut inventory = Inventory::new(5, 10);
+        for item in [arrow, cake, dart] {
+            inventory.add(item, &items).unwrap();
+        }
+        assert_eq!(inventory.slots_used(), 3);
+    }
+}
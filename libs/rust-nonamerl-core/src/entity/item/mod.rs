@@ -1,45 +1,74 @@
 #![allow(dead_code)]
 use std::fmt::Display;
 
+use serde::Deserialize;
+
 use crate::{Tile, TileSpriteInfo};
 
 use super::{
-    activator::UseKind,
+    activator::{UseKind, UseMode},
+    equipment::EquipSlot,
     property::HealthData,
+    status::StatusEffect,
     world::{ItemKey, World},
     WithId,
 };
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Damage {
     pub value: i32,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Defense {
     pub value: i32,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Weapon {
     pub damage: Option<Damage>,
     pub defense: Option<Defense>,
 }
 
+/// Anything worn rather than wielded: armour, helmets, shields, rings...
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Armor {
+    pub slot: EquipSlot,
+    pub defense: Option<Defense>,
+}
+
 // #[derive(Debug, Clone, Copy)]
 // pub struct Food {}
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Potion {
     pub health: HealthData,
+    /// Applied instead of healing when set, e.g. a potion of blindness.
+    pub effect: Option<StatusEffect>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ItemKind {
     Weapon(Weapon),
+    Armor(Armor),
     Potion(Potion),
     Food,
     Gold(u32),
+    /// Anything else, e.g. an urn or a key.
+    Misc,
+}
+
+/// What can be done with an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemBehaviour {
+    Equipable,
+    Melee,
+    Throwable,
+    Drinkable,
+    Edible,
+    /// A weapon that needs both hands.
+    TwoHanded,
 }
 
 #[derive(Debug)]
@@ -48,6 +77,11 @@ pub struct Item<T: Tile> {
     pub name: String,
     pub class: ItemKind,
     pub activators: Vec<UseKind<T>>,
+    pub weight: u32,
+    pub value: u32,
+    /// Uses left before the item breaks, `None` if it never wears out.
+    pub durability: Option<u32>,
+    pub behaviours: Vec<ItemBehaviour>,
 }
 
 impl<T: Tile> Item<T> {
@@ -57,13 +91,51 @@ impl<T: Tile> Item<T> {
             name: name.to_string(),
             class: ItemKind::Food,
             activators: vec![],
+            weight: 0,
+            value: 0,
+            durability: None,
+            behaviours: vec![],
         }
     }
 
+    pub fn has_behaviour(&self, behaviour: ItemBehaviour) -> bool {
+        self.behaviours.contains(&behaviour)
+    }
+
+    pub fn is_two_handed(&self) -> bool {
+        self.has_behaviour(ItemBehaviour::TwoHanded)
+    }
+
+    /// Whether the two items are identical and can share an inventory slot.
+    pub fn stacks_with(&self, other: &Item<T>) -> bool {
+        self.name == other.name
+            && self.class == other.class
+            && self.weight == other.weight
+            && self.value == other.value
+            && self.durability == other.durability
+            && self.behaviours.len() == other.behaviours.len()
+            && self
+                .behaviours
+                .iter()
+                .all(|behaviour| other.has_behaviour(*behaviour))
+    }
+
     pub fn get_activator(&self, pos: usize) -> Option<&UseKind<T>> {
         self.activators.get(pos)
     }
 
+    /// The first activator for `mode`.
+    pub fn activator(&self, mode: UseMode) -> Option<&UseKind<T>> {
+        self.activators.iter().find(|kind| kind.mode() == mode)
+    }
+
+    /// Whether the item is used up the first time it is used, e.g. a potion.
+    pub fn is_single_use(&self) -> bool {
+        matches!(self.class, ItemKind::Potion(_) | ItemKind::Food)
+            || self.has_behaviour(ItemBehaviour::Drinkable)
+            || self.has_behaviour(ItemBehaviour::Edible)
+    }
+
     fn sprite_info(&self) -> TileSpriteInfo {
         TileSpriteInfo::None
     }
@@ -87,11 +159,16 @@ impl<T: Tile> Display for Item<T> {
                 s.push_str(&format!("\nDamage: {:?}", weapon.damage));
                 s.push_str(&format!("\nDefense: {:?}", weapon.defense));
             }
+            ItemKind::Armor(armor) => {
+                s.push_str(&format!("\nSlot: {:?}", armor.slot));
+                s.push_str(&format!("\nDefense: {:?}", armor.defense));
+            }
             ItemKind::Food => {}
             ItemKind::Potion(_) => {}
             ItemKind::Gold(v) => {
                 s.push_str(&format!("\nGold: {}", v));
             }
+            ItemKind::Misc => {}
         }
         write!(f, "{}", s)
     }
@@ -103,6 +180,10 @@ pub struct ItemBuilder<T: Tile> {
     name: String,
     class: ItemKind,
     activators: Vec<UseKind<T>>,
+    weight: u32,
+    value: u32,
+    durability: Option<u32>,
+    behaviours: Vec<ItemBehaviour>,
 }
 
 impl<T: Tile> ItemBuilder<T> {
@@ -111,9 +192,33 @@ impl<T: Tile> ItemBuilder<T> {
             name,
             class: item_kind,
             activators: vec![],
+            weight: 0,
+            value: 0,
+            durability: None,
+            behaviours: vec![],
         }
     }
 
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn with_value(mut self, value: u32) -> Self {
+        self.value = value;
+        self
+    }
+
+    pub fn with_durability(mut self, durability: u32) -> Self {
+        self.durability = Some(durability);
+        self
+    }
+
+    pub fn add_behaviour(mut self, behaviour: ItemBehaviour) -> Self {
+        self.behaviours.push(behaviour);
+        self
+    }
+
     pub fn add_activator(mut self, activator: UseKind<T>) -> Self {
         self.activators.push(activator);
         self
@@ -123,6 +228,10 @@ impl<T: Tile> ItemBuilder<T> {
         let k = world.items.borrow_mut().add(&self.name, move |item| {
             item.class = self.class;
             item.activators = self.activators.clone();
+            item.weight = self.weight;
+            item.value = self.value;
+            item.durability = self.durability;
+            item.behaviours = self.behaviours.clone();
         });
 
         k
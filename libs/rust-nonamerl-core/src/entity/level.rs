@@ -0,0 +1,173 @@
+use super::component::Component;
+
+/// The total XP needed to reach each level: `base * (level - 1) ^ exponent`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XpCurve {
+    pub base: i32,
+    pub exponent: f32,
+}
+
+impl XpCurve {
+    pub fn xp_for(&self, level: u32) -> i32 {
+        if level <= 1 {
+            return 0;
+        }
+        (self.base as f32 * ((level - 1) as f32).powf(self.exponent)).round() as i32
+    }
+
+    /// The level reached with `xp`, from 1.
+    pub fn level_for(&self, xp: i32) -> u32 {
+        let mut level = 1;
+        while self.xp_for(level + 1) <= xp && self.xp_for(level + 1) > self.xp_for(level) {
+            level += 1;
+        }
+        level
+    }
+}
+
+/// The stats gained on each level up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Growth {
+    /// Added to both the current and the maximum health.
+    pub health: i32,
+    pub damage: i32,
+    pub defense: i32,
+}
+
+/// How entities gain experience and grow, see `World::award_xp`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progression {
+    pub curve: XpCurve,
+    pub growth: Growth,
+    /// New cells an entity must see to earn 1 XP, 0 to give no XP for exploring.
+    pub cells_per_xp: u32,
+}
+
+impl Default for Progression {
+    fn default() -> Self {
+        Self {
+            curve: XpCurve {
+                base: 20,
+                exponent: 1.5,
+            },
+            growth: Growth {
+                health: 5,
+                damage: 1,
+                defense: 0,
+            },
+            cells_per_xp: 20,
+        }
+    }
+}
+
+/// The experience level of an entity that levels up. Entities without it only collect `Xp`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Level {
+    pub level: u32,
+    /// Damage gained by levelling up, added to every melee attack.
+    pub damage: i32,
+    /// Defense gained by levelling up.
+    pub defense: i32,
+    /// New cells seen that did not earn XP yet.
+    pub(crate) explored: u32,
+}
+
+impl Component for Level {
+    const NAME: &'static str = "level";
+}
+
+impl Level {
+    pub fn new() -> Self {
+        Self {
+            level: 1,
+            damage: 0,
+            defense: 0,
+            explored: 0,
+        }
+    }
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::{
+            component::{Health, Xp},
+            event::WorldEvent,
+            world::World,
+        },
+        test_support::TestTile,
+        Dimension2D, IntExtent2D, IntVector2, Map,
+    };
+
+    #[test]
+    fn test_curve() {
+        let curve = Progression::default().curve;
+        assert_eq!(curve.xp_for(1), 0);
+        assert_eq!(curve.xp_for(2), 20);
+        assert_eq!(curve.xp_for(3), 57);
+        assert_eq!(curve.level_for(19), 1);
+        assert_eq!(curve.level_for(57), 3);
+    }
+
+    #[test]
+    fn test_level_up_from_xp_and_exploration() {
+        let mut world = World::<TestTile>::new();
+        world.progression.cells_per_xp = 2;
+        let hero = world.spawn("Hero", |entity| {
+            entity.insert(Health::new(10));
+            entity.insert(Level::new());
+        });
+        let rat = world.spawn("Rat", |_| {});
+
+        assert_eq!(world.award_xp(hero, 60), 2);
+        world.award_xp(rat, 60);
+        {
+            let entities = world.entities.borrow();
+            let hero = entities.get(hero).unwrap();
+            assert_eq!(
+                hero.get::<Level>(),
+                Some(&Level {
+                    level: 3,
+                    damage: 2,
+                    defense: 0,
+                    explored: 0
+                })
+            );
+            assert_eq!(hero.get::<Health>(), Some(&Health::new(20)));
+            assert_eq!(entities.get(rat).unwrap().get::<Xp>(), Some(&Xp(60)));
+            assert!(!entities.get(rat).unwrap().has::<Level>());
+        }
+        assert_eq!(
+            world.take_events(),
+            vec![
+                WorldEvent::LevelUp {
+                    entity: hero,
+                    level: 2
+                },
+                WorldEvent::LevelUp {
+                    entity: hero,
+                    level: 3
+                },
+            ]
+        );
+
+        let mut map = Map::new(IntExtent2D::new(0, 0, 5, 1), Dimension2D::new(1, 1));
+        for x in 0..5 {
+            map.set(x, 0, TestTile::default());
+        }
+        let cells = (0..5).map(|x| IntVector2::new(x, 0)).collect::<Vec<_>>();
+        assert_eq!(world.remember(hero, &map, cells.iter().copied()), 5);
+        assert_eq!(world.remember(hero, &map, cells.iter().copied()), 0);
+        let entities = world.entities.borrow();
+        let hero = entities.get(hero).unwrap();
+        assert_eq!(hero.get::<Xp>(), Some(&Xp(62)));
+        assert_eq!(hero.get::<Level>().unwrap().explored, 1);
+    }
+}
@@ -1,8 +1,17 @@
 pub mod action;
 pub mod activator;
+pub mod component;
 pub mod entity;
+pub mod equipment;
+pub mod event;
+pub mod inventory;
 pub mod item;
+pub mod level;
 pub mod property;
+pub mod query;
+pub mod spatial;
+pub mod status;
+pub mod template;
 pub mod world;
 
 pub trait WithId<K: slotmap::Key, V> {
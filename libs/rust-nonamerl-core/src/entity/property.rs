@@ -1,59 +1,55 @@
 #![allow(dead_code)]
-use std::collections::HashMap;
-
 use crate::IntVector2;
 
-use super::world::ItemKey;
+use super::{
+    component::{AnyComponent, Blocking, Component, Faction, Gold, Health, Name, Position, Xp},
+    equipment::Equipment,
+};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct HealthData {
     pub health: i32,
 }
 
-pub trait PropertyValue {
-    type ValueType;
-    fn value(&self) -> Value<Self::ValueType>;
-}
-
+/// A built-in component as a plain value, for code that picks components at runtime,
+/// e.g. from data files. Add it with `Entity::add_property`.
 #[derive(Debug, Clone)]
 pub enum Property {
-    Health(HealthData),
+    Health(Health),
     Xp(i32),
     Name(String),
-    Equip(ItemKey),
+    Equipment(Equipment),
     Position(IntVector2),
     Gold(i32),
+    Blocking,
+    Faction(Faction),
 }
 
 impl Property {
-    pub const XP: &'static str = "xp";
-    pub const HEALTH: &'static str = "health";
-    pub const NAME: &'static str = "name";
-    pub const EQUIP: &'static str = "equip";
-    pub const POSITION: &'static str = "position";
-    pub const GOLD: &'static str = "gold";
-
     pub fn name(&self) -> &'static str {
         match self {
-            Property::Health(_) => Property::HEALTH,
-            Property::Xp(_) => Property::XP,
-            Property::Name(_) => Property::NAME,
-            Property::Equip(_) => Property::EQUIP,
-            Property::Position(_) => Property::POSITION,
-            Property::Gold(_) => Property::GOLD,
+            Property::Health(_) => Health::NAME,
+            Property::Xp(_) => Xp::NAME,
+            Property::Name(_) => Name::NAME,
+            Property::Equipment(_) => Equipment::NAME,
+            Property::Position(_) => Position::NAME,
+            Property::Gold(_) => Gold::NAME,
+            Property::Blocking => Blocking::NAME,
+            Property::Faction(_) => Faction::NAME,
         }
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct Value<T>(T);
-
-impl PropertyValue for HealthData {
-    type ValueType = i32;
-
-    fn value(&self) -> Value<Self::ValueType> {
-        let HealthData { health } = self;
-        Value(*health)
+    pub fn into_component(self) -> Box<dyn AnyComponent> {
+        match self {
+            Property::Health(health) => Box::new(health),
+            Property::Xp(xp) => Box::new(Xp(xp)),
+            Property::Name(name) => Box::new(Name(name)),
+            Property::Equipment(equipment) => Box::new(equipment),
+            Property::Position(pos) => Box::new(Position(pos)),
+            Property::Gold(gold) => Box::new(Gold(gold)),
+            Property::Blocking => Box::new(Blocking),
+            Property::Faction(faction) => Box::new(faction),
+        }
     }
 }
 
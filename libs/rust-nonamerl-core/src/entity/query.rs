@@ -0,0 +1,101 @@
+use super::{component::Component, entity::Entity};
+
+/// The components a world query looks for, a single component type or a tuple of them,
+/// e.g. `(Position, Health)`.
+pub trait Query {
+    type Item<'a>;
+
+    /// The components of `entity`, or `None` if it lacks any of them.
+    fn fetch(entity: &Entity) -> Option<Self::Item<'_>>;
+
+    fn matches(entity: &Entity) -> bool {
+        Self::fetch(entity).is_some()
+    }
+}
+
+impl<C: Component> Query for C {
+    type Item<'a> = &'a C;
+
+    fn fetch(entity: &Entity) -> Option<Self::Item<'_>> {
+        entity.get::<C>()
+    }
+
+    fn matches(entity: &Entity) -> bool {
+        entity.has::<C>()
+    }
+}
+
+macro_rules! tuple_query {
+    ($($name:ident),+) => {
+        impl<$($name: Query),+> Query for ($($name,)+) {
+            type Item<'a> = ($($name::Item<'a>,)+);
+
+            fn fetch(entity: &Entity) -> Option<Self::Item<'_>> {
+                Some(($($name::fetch(entity)?,)+))
+            }
+
+            fn matches(entity: &Entity) -> bool {
+                $($name::matches(entity))&&+
+            }
+        }
+    };
+}
+
+tuple_query!(A);
+tuple_query!(A, B);
+tuple_query!(A, B, C);
+tuple_query!(A, B, C, D);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        component::{Health, Position, Xp},
+        test_support::TestTile,
+        world::World,
+        IntVector2,
+    };
+
+    #[test]
+    fn test_world_queries() {
+        let world = World::<TestTile>::new();
+        let a = world.spawn("A", |entity| {
+            entity.insert(Position(IntVector2::new(0, 0)));
+            entity.insert(Health::new(10));
+        });
+        let b = world.spawn("B", |entity| {
+            entity.insert(Position(IntVector2::new(3, 3)));
+            entity.insert(Health::new(5));
+            entity.insert(Xp(2));
+        });
+        world.spawn("C", |entity| {
+            entity.insert(Position(IntVector2::new(1, 0)));
+        });
+
+        assert_eq!(world.query::<(Position, Health)>(), vec![a, b]);
+        assert_eq!(world.query::<(Health, Xp)>(), vec![b]);
+
+        let mut total = 0;
+        world.each::<(Health, Xp), _>(|_, (health, xp)| total += health.current + xp.0);
+        assert_eq!(total, 7);
+
+        world.each_mut::<Health, _>(|_, entity| entity.get_mut::<Health>().unwrap().current -= 1);
+        assert_eq!(
+            world.entities.borrow().get(a).unwrap().get::<Health>(),
+            Some(&Health {
+                current: 9,
+                max: 10
+            })
+        );
+
+        assert_eq!(
+            world.filter(|_, entity| entity.get::<Health>().is_some_and(|h| h.current < 5)),
+            vec![b]
+        );
+
+        let mut near = world.within_radius(IntVector2::new(0, 0), 2);
+        near.sort();
+        assert_eq!(near.len(), 2);
+        assert!(!near.contains(&b));
+        assert!(world.within_radius(IntVector2::new(0, 0), -1).is_empty());
+    }
+}
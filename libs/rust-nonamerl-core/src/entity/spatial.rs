@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use crate::{IntExtent2D, IntVector2, Vec2};
+
+use super::world::EntityKey;
+
+/// Keeps track of which entities stand on which map cell.
+///
+/// The index mirrors the `Position` component of the entities: it is filled by
+/// `World::spawn` and kept up to date by the actions that move entities around.
+#[derive(Debug, Clone, Default)]
+pub struct SpatialIndex {
+    cells: HashMap<IntVector2, Vec<EntityKey>>,
+    positions: HashMap<EntityKey, IntVector2>,
+}
+
+impl SpatialIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Places `entity` at `pos`, moving it if it was already indexed.
+    pub fn insert(&mut self, entity: EntityKey, pos: IntVector2) {
+        self.remove(entity);
+        self.cells.entry(pos).or_default().push(entity);
+        self.positions.insert(entity, pos);
+    }
+
+    /// Removes `entity` from the index, returning the cell it was on.
+    pub fn remove(&mut self, entity: EntityKey) -> Option<IntVector2> {
+        let pos = self.positions.remove(&entity)?;
+        if let Some(cell) = self.cells.get_mut(&pos) {
+            cell.retain(|e| *e != entity);
+            if cell.is_empty() {
+                self.cells.remove(&pos);
+            }
+        }
+        Some(pos)
+    }
+
+    pub fn move_entity(&mut self, entity: EntityKey, to: IntVector2) {
+        self.insert(entity, to);
+    }
+
+    pub fn position(&self, entity: EntityKey) -> Option<IntVector2> {
+        self.positions.get(&entity).copied()
+    }
+
+    /// The entities standing on `pos`, in the order they arrived there.
+    pub fn at(&self, pos: IntVector2) -> &[EntityKey] {
+        self.cells.get(&pos).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn is_occupied(&self, pos: IntVector2) -> bool {
+        self.cells.contains_key(&pos)
+    }
+
+    /// Every entity whose cell lies inside `extent`, with its position.
+    pub fn in_rect(&self, extent: &IntExtent2D) -> Vec<(EntityKey, IntVector2)> {
+        self.positions
+            .iter()
+            .filter(|(_, pos)| extent.contains(pos.x(), pos.y()))
+            .map(|(entity, pos)| (*entity, *pos))
+            .collect()
+    }
+
+    /// Finds the entity closest to `pos` for which `filter` returns `true`.
+    ///
+    /// Distance is measured in moves (8-way, Chebyshev); ties are broken by the
+    /// straight-line distance. Cells farther than `max_radius` are not searched.
+    pub fn nearest<F: Fn(EntityKey) -> bool>(
+        &self,
+        pos: IntVector2,
+        max_radius: i32,
+        filter: F,
+    ) -> Option<(EntityKey, IntVector2)> {
+        for radius in 0..=max_radius {
+            let found = ring(pos, radius)
+                .flat_map(|cell| self.at(cell).iter().map(move |e| (*e, cell)))
+                .filter(|(entity, _)| filter(*entity))
+                .min_by_key(|(_, cell)| {
+                    let dx = cell.x() - pos.x();
+                    let dy = cell.y() - pos.y();
+                    dx * dx + dy * dy
+                });
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.positions.clear();
+    }
+}
+
+/// The cells at exactly `radius` moves from `center`.
+fn ring(center: IntVector2, radius: i32) -> impl Iterator<Item = IntVector2> {
+    (-radius..=radius).flat_map(move |dy| {
+        (-radius..=radius)
+            .filter(move |dx| dy.abs() == radius || dx.abs() == radius)
+            .map(move |dx| IntVector2::new(center.x() + dx, center.y() + dy))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use slotmap::SlotMap;
+
+    use super::*;
+
+    fn keys(n: usize) -> Vec<EntityKey> {
+        let mut map = SlotMap::<EntityKey, ()>::with_key();
+        (0..n).map(|_| map.insert(())).collect()
+    }
+
+    #[test]
+    fn test_insert_move_remove() {
+        let e = keys(2);
+        let mut index = SpatialIndex::new();
+        index.insert(e[0], IntVector2::new(1, 1));
+        index.insert(e[1], IntVector2::new(1, 1));
+        assert_eq!(index.at(IntVector2::new(1, 1)), &[e[0], e[1]]);
+
+        index.move_entity(e[0], IntVector2::new(2, 1));
+        assert_eq!(index.at(IntVector2::new(1, 1)), &[e[1]]);
+        assert_eq!(index.position(e[0]), Some(IntVector2::new(2, 1)));
+
+        assert_eq!(index.remove(e[1]), Some(IntVector2::new(1, 1)));
+        assert!(!index.is_occupied(IntVector2::new(1, 1)));
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    fn test_rect_and_nearest() {
+        let e = keys(3);
+        let mut index = SpatialIndex::new();
+        index.insert(e[0], IntVector2::new(0, 0));
+        index.insert(e[1], IntVector2::new(3, 3));
+        index.insert(e[2], IntVector2::new(5, 0));
+
+        let mut found = index.in_rect(&IntExtent2D::new(0, 0, 4, 4));
+        found.sort_by_key(|(_, pos)| pos.x());
+        assert_eq!(
+            found,
+            vec![(e[0], IntVector2::new(0, 0)), (e[1], IntVector2::new(3, 3))]
+        );
+
+        let center = IntVector2::new(4, 1);
+        assert_eq!(index.nearest(center, 10, |_| true).map(|f| f.0), Some(e[2]));
+        assert_eq!(
+            index.nearest(center, 10, |k| k != e[2]).map(|f| f.0),
+            Some(e[1])
+        );
+        assert_eq!(index.nearest(center, 0, |_| true), None);
+    }
+}
@@ -0,0 +1,218 @@
+use serde::Deserialize;
+
+use super::component::Component;
+
+/// A timed condition affecting an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusKind {
+    /// Loses `potency` health every turn.
+    Poison,
+    /// Recovers `potency` health every turn.
+    Regeneration,
+    /// `potency` more speed, cancels `Slow`.
+    Haste,
+    /// `potency` less speed, cancels `Haste`.
+    Slow,
+    /// Sees no further than the adjacent cells.
+    Blind,
+    /// Sometimes moves in a random direction, see `CONFUSED_STUMBLE_CHANCE`.
+    Confused,
+}
+
+/// What happens when an effect is applied to an entity already under the same kind of effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackRule {
+    /// Keeps the longest duration and the strongest potency.
+    Refresh,
+    /// Adds the durations.
+    Extend,
+    /// Adds the potencies and keeps the longest duration.
+    Intensify,
+}
+
+impl StatusKind {
+    pub fn stack_rule(&self) -> StackRule {
+        match self {
+            StatusKind::Poison => StackRule::Intensify,
+            StatusKind::Regeneration | StatusKind::Haste | StatusKind::Slow => StackRule::Refresh,
+            StatusKind::Blind | StatusKind::Confused => StackRule::Extend,
+        }
+    }
+
+    /// The kind removed when this one is applied.
+    pub fn cancels(&self) -> Option<StatusKind> {
+        match self {
+            StatusKind::Haste => Some(StatusKind::Slow),
+            StatusKind::Slow => Some(StatusKind::Haste),
+            _ => None,
+        }
+    }
+}
+
+/// Chance, from 0 to 1, that a confused entity moves in a random direction.
+pub const CONFUSED_STUMBLE_CHANCE: f32 = 0.5;
+
+fn default_potency() -> i32 {
+    1
+}
+
+/// One effect, as applied by a potion, a trap or a spell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// Turns left, the effect acts on each of them.
+    pub turns: u32,
+    #[serde(default = "default_potency")]
+    pub potency: i32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, turns: u32, potency: i32) -> Self {
+        Self {
+            kind,
+            turns,
+            potency,
+        }
+    }
+}
+
+/// The effects an entity is under, at most one per `StatusKind`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl Component for StatusEffects {
+    const NAME: &'static str = "status_effects";
+}
+
+impl StatusEffects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `effect`, combined with an effect of the same kind following its `StackRule`.
+    pub fn apply(&mut self, effect: StatusEffect) {
+        if let Some(cancelled) = effect.kind.cancels() {
+            self.remove(cancelled);
+        }
+        match self.effects.iter_mut().find(|e| e.kind == effect.kind) {
+            Some(current) => match effect.kind.stack_rule() {
+                StackRule::Refresh => {
+                    current.turns = current.turns.max(effect.turns);
+                    current.potency = current.potency.max(effect.potency);
+                }
+                StackRule::Extend => current.turns += effect.turns,
+                StackRule::Intensify => {
+                    current.turns = current.turns.max(effect.turns);
+                    current.potency += effect.potency;
+                }
+            },
+            None => self.effects.push(effect),
+        }
+    }
+
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|e| e.kind == kind)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    pub fn remove(&mut self, kind: StatusKind) -> Option<StatusEffect> {
+        let index = self.effects.iter().position(|e| e.kind == kind)?;
+        Some(self.effects.remove(index))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.effects.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Counts down one turn. Returns the effects that acted this turn and the kinds that
+    /// ran out, which are removed.
+    pub fn tick(&mut self) -> (Vec<StatusEffect>, Vec<StatusKind>) {
+        let active = self.effects.clone();
+        self.effects
+            .iter_mut()
+            .for_each(|e| e.turns = e.turns.saturating_sub(1));
+        let expired = self
+            .effects
+            .iter()
+            .filter(|e| e.turns == 0)
+            .map(|e| e.kind)
+            .collect();
+        self.effects.retain(|e| e.turns > 0);
+        (active, expired)
+    }
+
+    /// The speed of an entity moving `base` fast, changed by `Haste` and `Slow`. Never less
+    /// than 1, so a slowed entity still acts now and then.
+    pub fn speed(&self, base: i32) -> i32 {
+        let bonus: i32 = self
+            .effects
+            .iter()
+            .map(|e| match e.kind {
+                StatusKind::Haste => e.potency,
+                StatusKind::Slow => -e.potency,
+                _ => 0,
+            })
+            .sum();
+        (base + bonus).max(1)
+    }
+
+    /// The FOV radius of an entity seeing `base` cells far.
+    pub fn fov_radius(&self, base: i32) -> i32 {
+        if self.has(StatusKind::Blind) {
+            base.min(1)
+        } else {
+            base
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stacking_and_expiry() {
+        let mut effects = StatusEffects::new();
+        effects.apply(StatusEffect::new(StatusKind::Poison, 3, 1));
+        effects.apply(StatusEffect::new(StatusKind::Poison, 2, 2));
+        effects.apply(StatusEffect::new(StatusKind::Blind, 1, 1));
+        effects.apply(StatusEffect::new(StatusKind::Blind, 1, 1));
+        effects.apply(StatusEffect::new(StatusKind::Haste, 1, 2));
+        effects.apply(StatusEffect::new(StatusKind::Regeneration, 4, 1));
+        effects.apply(StatusEffect::new(StatusKind::Regeneration, 2, 3));
+        assert_eq!(
+            effects.get(StatusKind::Poison),
+            Some(&StatusEffect::new(StatusKind::Poison, 3, 3))
+        );
+        assert_eq!(effects.get(StatusKind::Blind).unwrap().turns, 2);
+        assert_eq!(
+            effects.get(StatusKind::Regeneration),
+            Some(&StatusEffect::new(StatusKind::Regeneration, 4, 3))
+        );
+        assert_eq!(effects.speed(10), 12);
+        assert_eq!(effects.fov_radius(8), 1);
+
+        effects.apply(StatusEffect::new(StatusKind::Slow, 5, 1));
+        assert!(!effects.has(StatusKind::Haste));
+        assert_eq!(effects.speed(10), 9);
+        assert_eq!(effects.speed(1), 1);
+
+        let (active, expired) = effects.tick();
+        assert_eq!(active.len(), 4);
+        assert!(expired.is_empty());
+        let (_, expired) = effects.tick();
+        assert_eq!(expired, vec![StatusKind::Blind]);
+        assert_eq!(effects.fov_radius(8), 8);
+    }
+}
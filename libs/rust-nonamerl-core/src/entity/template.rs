@@ -0,0 +1,495 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs,
+    path::Path,
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::{IntVector2, Tile};
+
+use super::{
+    activator::{drink_potion, UseKind},
+    component::{Blocking, Corpse, Faction, Gold, Health, Position, XpReward},
+    equipment::{EquipSlot, Equipment},
+    item::{Armor, Damage, Defense, ItemBehaviour, ItemBuilder, ItemKind, Potion, Weapon},
+    level::Level,
+    property::HealthData,
+    status::StatusEffect,
+    world::{EntityKey, ItemKey, World},
+};
+
+#[derive(Debug)]
+pub enum TemplateError {
+    Io(std::io::Error),
+    /// The JSON does not match the template format. `path` points at the bad field,
+    /// e.g. `sword.damage`.
+    Parse {
+        path: String,
+        error: serde_json::Error,
+    },
+    /// A field has a value the game cannot use.
+    Invalid {
+        path: String,
+        reason: String,
+    },
+    UnknownTemplate(String),
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Io(e) => write!(f, "cannot read templates: {}", e),
+            TemplateError::Parse { path, error } => {
+                write!(f, "invalid template at `{}`: {}", path, error)
+            }
+            TemplateError::Invalid { path, reason } => {
+                write!(f, "invalid template at `{}`: {}", path, reason)
+            }
+            TemplateError::UnknownTemplate(name) => write!(f, "unknown template `{}`", name),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl From<std::io::Error> for TemplateError {
+    fn from(e: std::io::Error) -> Self {
+        TemplateError::Io(e)
+    }
+}
+
+/// A definition read from a template file.
+pub trait Template: DeserializeOwned {
+    /// Checks the values serde cannot, returning the bad field and why it is wrong.
+    fn validate(&self) -> Result<(), (&'static str, String)> {
+        Ok(())
+    }
+}
+
+/// Named templates, as found in files such as `data/weapons.json`:
+/// `{ "sword": { ... }, "stone": { ... } }`.
+#[derive(Debug, Clone)]
+pub struct Templates<D: Template> {
+    templates: HashMap<String, D>,
+}
+
+pub type ItemTemplates = Templates<ItemTemplate>;
+pub type EntityTemplates = Templates<EntityTemplate>;
+
+impl<D: Template> Templates<D> {
+    pub fn from_json(json: &str) -> Result<Self, TemplateError> {
+        let deserializer = &mut serde_json::Deserializer::from_str(json);
+        let templates: HashMap<String, D> = serde_path_to_error::deserialize(deserializer)
+            .map_err(|e| TemplateError::Parse {
+                path: e.path().to_string(),
+                error: e.into_inner(),
+            })?;
+        for (name, template) in templates.iter() {
+            template
+                .validate()
+                .map_err(|(field, reason)| TemplateError::Invalid {
+                    path: format!("{}.{}", name, field),
+                    reason,
+                })?;
+        }
+        Ok(Self { templates })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, TemplateError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&D> {
+        self.templates.get(name)
+    }
+
+    fn require(&self, name: &str) -> Result<&D, TemplateError> {
+        self.get(name)
+            .ok_or_else(|| TemplateError::UnknownTemplate(name.to_owned()))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemClass {
+    /// An item with no special use, mapped to `ItemKind::Misc`.
+    Item,
+    Weapon,
+    Armor,
+    Potion,
+    Food,
+    Gold,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemTemplate {
+    pub name: String,
+    pub class: ItemClass,
+    /// Free-form subtype, e.g. `urna`.
+    #[serde(rename = "type", default)]
+    pub subtype: Option<String>,
+    #[serde(default)]
+    pub damage: Option<i32>,
+    #[serde(default)]
+    pub defense: Option<i32>,
+    /// Where an armor is worn.
+    #[serde(default)]
+    pub slot: Option<EquipSlot>,
+    /// Health restored by a potion.
+    #[serde(default)]
+    pub heal: Option<i32>,
+    /// Status effect applied by a potion, instead of healing.
+    #[serde(default)]
+    pub effect: Option<StatusEffect>,
+    /// Coins in a gold pile.
+    #[serde(default)]
+    pub amount: Option<u32>,
+    #[serde(default)]
+    pub durability: Option<u32>,
+    #[serde(default)]
+    pub value: u32,
+    #[serde(default)]
+    pub weight: u32,
+    #[serde(default)]
+    pub behaviours: Vec<ItemBehaviour>,
+}
+
+impl Template for ItemTemplate {
+    fn validate(&self) -> Result<(), (&'static str, String)> {
+        if self.damage.is_some_and(|damage| damage < 0) {
+            return Err(("damage", "must not be negative".to_owned()));
+        }
+        if self.defense.is_some_and(|defense| defense < 0) {
+            return Err(("defense", "must not be negative".to_owned()));
+        }
+        if self.durability == Some(0) {
+            return Err(("durability", "must be greater than 0".to_owned()));
+        }
+        match self.class {
+            ItemClass::Weapon if self.damage.is_none() && self.defense.is_none() => {
+                Err(("damage", "a weapon needs a damage or a defense".to_owned()))
+            }
+            ItemClass::Armor if self.slot.is_none() => {
+                Err(("slot", "an armor needs a slot".to_owned()))
+            }
+            ItemClass::Armor if self.slot == Some(EquipSlot::MainHand) => Err((
+                "slot",
+                "an armor cannot be held in the main hand".to_owned(),
+            )),
+            ItemClass::Potion if self.heal.is_none() && self.effect.is_none() => Err((
+                "heal",
+                "a potion needs a heal value or an effect".to_owned(),
+            )),
+            ItemClass::Potion if self.heal.is_some() && self.effect.is_some() => Err((
+                "effect",
+                "a potion cannot both heal and have an effect".to_owned(),
+            )),
+            ItemClass::Gold if self.amount.is_none() => {
+                Err(("amount", "gold needs an amount".to_owned()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl ItemTemplate {
+    pub fn kind(&self) -> ItemKind {
+        match self.class {
+            ItemClass::Item => ItemKind::Misc,
+            ItemClass::Weapon => ItemKind::Weapon(Weapon {
+                damage: self.damage.map(|value| Damage { value }),
+                defense: self.defense.map(|value| Defense { value }),
+            }),
+            ItemClass::Armor => ItemKind::Armor(Armor {
+                slot: self.slot.unwrap_or(EquipSlot::Body),
+                defense: self.defense.map(|value| Defense { value }),
+            }),
+            ItemClass::Potion => ItemKind::Potion(Potion {
+                health: HealthData {
+                    health: self.heal.unwrap_or_default(),
+                },
+                effect: self.effect,
+            }),
+            ItemClass::Food => ItemKind::Food,
+            ItemClass::Gold => ItemKind::Gold(self.amount.unwrap_or_default()),
+        }
+    }
+
+    /// A builder for the item. Potions get the `drink_potion` activator.
+    pub fn builder<T: Tile>(&self) -> ItemBuilder<T> {
+        let builder = ItemBuilder::new(self.name.clone(), self.kind())
+            .with_weight(self.weight)
+            .with_value(self.value);
+        let builder = match self.class {
+            ItemClass::Potion => builder.add_activator(UseKind::Drink {
+                activate_fn: drink_potion,
+            }),
+            _ => builder,
+        };
+        let builder = match self.durability {
+            Some(durability) => builder.with_durability(durability),
+            None => builder,
+        };
+        self.behaviours.iter().fold(builder, |builder, behaviour| {
+            builder.add_behaviour(*behaviour)
+        })
+    }
+}
+
+impl Templates<ItemTemplate> {
+    /// Creates the item described by the template `name`.
+    pub fn build<T: Tile>(&self, name: &str, world: &World<T>) -> Result<ItemKey, TemplateError> {
+        Ok(self.require(name)?.builder().build(world))
+    }
+}
+
+fn default_faction() -> Faction {
+    Faction::Monster
+}
+
+fn default_blocking() -> bool {
+    true
+}
+
+/// A creature, as found in `data/monsters.json`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EntityTemplate {
+    pub name: String,
+    pub health: i32,
+    #[serde(default = "default_faction")]
+    pub faction: Faction,
+    #[serde(default = "default_blocking")]
+    pub blocking: bool,
+    /// Experience given when killed, as an `XpReward`.
+    #[serde(default)]
+    pub xp: i32,
+    #[serde(default)]
+    pub gold: Option<i32>,
+    /// The item templates the entity starts with equipped.
+    #[serde(default)]
+    pub equip: Vec<String>,
+    /// The name of the item left behind when it dies.
+    #[serde(default)]
+    pub corpse: Option<String>,
+    /// The level the entity starts at, with the stats grown to it. Entities without one
+    /// never level up.
+    #[serde(default)]
+    pub level: Option<u32>,
+}
+
+impl Template for EntityTemplate {
+    fn validate(&self) -> Result<(), (&'static str, String)> {
+        if self.health <= 0 {
+            return Err(("health", "must be greater than 0".to_owned()));
+        }
+        if self.gold.is_some_and(|gold| gold < 0) {
+            return Err(("gold", "must not be negative".to_owned()));
+        }
+        if self.level == Some(0) {
+            return Err(("level", "must be greater than 0".to_owned()));
+        }
+        Ok(())
+    }
+}
+
+impl Templates<EntityTemplate> {
+    /// Spawns the entity described by the template `name` at `pos`, creating its
+    /// equipment from `items`.
+    pub fn spawn<T: Tile>(
+        &self,
+        name: &str,
+        world: &World<T>,
+        pos: IntVector2,
+        items: &ItemTemplates,
+    ) -> Result<EntityKey, TemplateError> {
+        let template = self.require(name)?;
+        let mut equipment = Equipment::new();
+        for (i, item) in template.equip.iter().enumerate() {
+            let invalid = |reason: String| TemplateError::Invalid {
+                path: format!("{}.equip[{}]", name, i),
+                reason,
+            };
+            if items.get(item).is_none() {
+                return Err(invalid(format!("unknown item `{}`", item)));
+            }
+            let item = items.build(item, world)?;
+            equipment
+                .equip(item, None, &world.items.borrow())
+                .map_err(|e| invalid(e.to_string()))?;
+        }
+
+        let growth = world.progression.growth;
+        Ok(world.spawn(&template.name, |entity| {
+            let grown = template.level.map_or(0, |level| level as i32 - 1);
+            entity.insert(Position(pos));
+            entity.insert(Health::new(template.health + growth.health * grown));
+            if let Some(level) = template.level {
+                entity.insert(Level {
+                    level,
+                    damage: growth.damage * grown,
+                    defense: growth.defense * grown,
+                    ..Level::new()
+                });
+            }
+            entity.insert(template.faction);
+            entity.insert(XpReward(template.xp));
+            if template.blocking {
+                entity.insert(Blocking);
+            }
+            if let Some(gold) = template.gold {
+                entity.insert(Gold(gold));
+            }
+            if !equipment.is_empty() {
+                entity.insert(equipment.clone());
+            }
+            if let Some(corpse) = &template.corpse {
+                entity.insert(Corpse(corpse.clone()));
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{component::Xp, status::StatusKind, test_support::TestTile};
+
+    const ITEMS: &str = r#"{
+        "sword": { "name": "sword", "class": "weapon", "damage": 10, "durability": 100,
+                   "value": 10, "weight": 10, "behaviours": ["equipable", "melee"] },
+        "helmet": { "name": "helmet", "class": "armor", "slot": "head", "defense": 1 },
+        "blindness": { "name": "blindness potion", "class": "potion",
+                   "effect": { "kind": "blind", "turns": 10 } },
+        "urn": { "name": "urn", "class": "item", "type": "urna", "behaviours": ["throwable"] }
+    }"#;
+
+    const MONSTERS: &str = r#"{
+        "orc": { "name": "Orc", "health": 15, "xp": 5, "equip": ["sword", "helmet"], "level": 3 }
+    }"#;
+
+    fn error_path(result: Result<ItemTemplates, TemplateError>) -> String {
+        match result {
+            Err(TemplateError::Parse { path, .. }) | Err(TemplateError::Invalid { path, .. }) => {
+                path
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_build_from_templates() {
+        let world = World::<TestTile>::new();
+        let items = ItemTemplates::from_json(ITEMS).unwrap();
+        let monsters = EntityTemplates::from_json(MONSTERS).unwrap();
+
+        let sword = items.build("sword", &world).unwrap();
+        {
+            let items = world.items.borrow();
+            let sword = items.get(sword).unwrap();
+            assert_eq!(sword.weight, 10);
+            assert_eq!(sword.durability, Some(100));
+            assert!(sword.has_behaviour(ItemBehaviour::Melee));
+            assert!(matches!(sword.class, ItemKind::Weapon(_)));
+        }
+
+        let blindness = items.build("blindness", &world).unwrap();
+        assert!(matches!(
+            world.items.borrow().get(blindness).unwrap().class,
+            ItemKind::Potion(Potion {
+                effect: Some(StatusEffect {
+                    kind: StatusKind::Blind,
+                    turns: 10,
+                    potency: 1
+                }),
+                ..
+            })
+        ));
+
+        let orc = monsters
+            .spawn("orc", &world, IntVector2::new(2, 3), &items)
+            .unwrap();
+        let entities = world.entities.borrow();
+        let orc = entities.get(orc).unwrap();
+        assert_eq!(orc.name(), "Orc");
+        assert_eq!(orc.get::<Health>(), Some(&Health::new(25)));
+        assert_eq!(
+            orc.get::<Level>().map(|level| (level.level, level.damage)),
+            Some((3, 2))
+        );
+        assert_eq!(orc.get::<Faction>(), Some(&Faction::Monster));
+        assert_eq!(orc.get::<XpReward>(), Some(&XpReward(5)));
+        assert!(!orc.has::<Xp>());
+        assert!(orc.has::<Blocking>());
+        assert_eq!(
+            orc.get::<Equipment>()
+                .unwrap()
+                .bonus(&world.items.borrow())
+                .defense,
+            1
+        );
+        assert_eq!(world.items.borrow().len(), 4);
+        assert!(matches!(
+            items.build("axe", &world),
+            Err(TemplateError::UnknownTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_errors_point_at_the_field() {
+        assert_eq!(
+            error_path(ItemTemplates::from_json(
+                r#"{ "sword": { "name": "sword", "class": "weapon", "damage": "ten" } }"#
+            )),
+            "sword.damage"
+        );
+        assert_eq!(
+            error_path(ItemTemplates::from_json(
+                r#"{ "urn": { "name": "urn", "class": "item", "behaviours": ["throwable", "edble"] } }"#
+            )),
+            "urn.behaviours[1]"
+        );
+        assert_eq!(
+            error_path(ItemTemplates::from_json(
+                r#"{ "stick": { "name": "stick", "class": "weapon" } }"#
+            )),
+            "stick.damage"
+        );
+        assert_eq!(
+            error_path(ItemTemplates::from_json(
+                r#"{ "mail": { "name": "mail", "class": "armor", "defense": 2 } }"#
+            )),
+            "mail.slot"
+        );
+
+        let monsters = EntityTemplates::from_json(
+            r#"{ "rat": { "name": "Rat", "health": 2, "equip": ["axe"] },
+                "bat": { "name": "Bat", "health": 2, "equip": ["urn"] } }"#,
+        )
+        .unwrap();
+        let world = World::<TestTile>::new();
+        assert!(matches!(
+            monsters.spawn("rat", &world, IntVector2::new(0, 0), &ItemTemplates::from_json("{}").unwrap()),
+            Err(TemplateError::Invalid { path, .. }) if path == "rat.equip[0]"
+        ));
+        assert!(matches!(
+            monsters.spawn("bat", &world, IntVector2::new(0, 0), &ItemTemplates::from_json(ITEMS).unwrap()),
+            Err(TemplateError::Invalid { path, .. }) if path == "bat.equip[0]"
+        ));
+    }
+}
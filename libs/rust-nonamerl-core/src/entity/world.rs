@@ -1,13 +1,27 @@
 #![allow(dead_code)]
-use std::cell::RefCell;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use rand::{rngs::StdRng, SeedableRng};
 
 use slotmap::new_key_type;
 
-use crate::{entity::WithId, Tile};
+use crate::{
+    entity::WithId, Action, DamageAction, HealAction, IntExtent2D, IntVector2, Map, MapMemory,
+    Tile, Triggers, Vec2,
+};
 
 use super::{
+    component::{Energy, Health, Position, Xp, ACTION_COST},
     entity::Entity,
+    event::WorldEvent,
     item::{Item, ItemBuilder},
+    level::{Level, Progression},
+    query::Query,
+    spatial::SpatialIndex,
+    status::{StatusEffects, StatusKind},
 };
 
 new_key_type! { pub struct EntityKey; }
@@ -45,20 +59,324 @@ impl<K: slotmap::Key, V: WithId<K, V>> SlotMapStorage<K, V> {
     pub fn remove(&mut self, key: K) -> Option<V> {
         self.data.remove(key)
     }
+
+    pub fn iter(&self) -> slotmap::basic::Iter<'_, K, V> {
+        self.data.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> slotmap::basic::IterMut<'_, K, V> {
+        self.data.iter_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
 }
 
 #[derive(Debug)]
 pub struct World<T: Tile> {
     pub entities: RefCell<SlotMapStorage<EntityKey, Entity>>,
     pub items: RefCell<SlotMapStorage<ItemKey, Item<T>>>,
+    pub spatial: RefCell<SpatialIndex>,
+    /// What each entity has seen of the map, see `World::remember`.
+    pub memories: RefCell<HashMap<EntityKey, MapMemory<T>>>,
+    /// Traps and other triggers laid on the map, fired by the actions moving entities.
+    pub triggers: RefCell<Triggers<T>>,
+    /// The source of every random roll made by actions, seed it for reproducible runs.
+    pub rng: RefCell<StdRng>,
+    /// How entities gain experience and levels.
+    pub progression: Progression,
+    events: RefCell<Vec<WorldEvent>>,
+    game_over: Cell<bool>,
 }
 
 impl<T: Tile> World<T> {
     pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    /// A world whose random rolls are the same on every run.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> Self {
         Self {
             entities: RefCell::new(SlotMapStorage::new()),
             items: RefCell::new(SlotMapStorage::new()),
+            spatial: RefCell::new(SpatialIndex::new()),
+            memories: RefCell::new(HashMap::new()),
+            triggers: RefCell::new(Triggers::new()),
+            rng: RefCell::new(rng),
+            progression: Progression::default(),
+            events: RefCell::new(Vec::new()),
+            game_over: Cell::new(false),
+        }
+    }
+
+    /// Ends the run, e.g. when the player dies.
+    pub fn end_run(&self) {
+        self.game_over.set(true);
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_over.get()
+    }
+
+    pub fn emit(&self, event: WorldEvent) {
+        self.events.borrow_mut().push(event);
+    }
+
+    /// Drains the events emitted since the previous call, oldest first.
+    pub fn take_events(&self) -> Vec<WorldEvent> {
+        std::mem::take(&mut *self.events.borrow_mut())
+    }
+
+    /// Adds an entity and registers it in the spatial index if it has a position.
+    pub fn spawn<F: Fn(&mut Entity)>(&self, name: &str, setup_fn: F) -> EntityKey {
+        let key = self.entities.borrow_mut().add(name, setup_fn);
+        self.reindex(key);
+        key
+    }
+
+    /// Removes an entity from the world and from the spatial index.
+    pub fn despawn(&self, key: EntityKey) -> Option<Entity> {
+        self.spatial.borrow_mut().remove(key);
+        self.memories.borrow_mut().remove(&key);
+        self.entities.borrow_mut().remove(key)
+    }
+
+    /// Updates the map memory of `key` with the cells it can currently see. Returns the
+    /// number of cells seen for the first time.
+    ///
+    /// An entity with a `Level` earns 1 XP every `Progression::cells_per_xp` new cells.
+    pub fn remember<I: IntoIterator<Item = IntVector2>>(
+        &self,
+        key: EntityKey,
+        map: &Map<T>,
+        visible: I,
+    ) -> usize {
+        let discovered = self
+            .memories
+            .borrow_mut()
+            .entry(key)
+            .or_default()
+            .update(map, visible);
+
+        let cells_per_xp = self.progression.cells_per_xp;
+        let xp = self
+            .entities
+            .borrow_mut()
+            .get_mut(key)
+            .and_then(|entity| entity.get_mut::<Level>())
+            .filter(|_| cells_per_xp > 0)
+            .map(|level| {
+                level.explored += discovered as u32;
+                let xp = level.explored / cells_per_xp;
+                level.explored %= cells_per_xp;
+                xp as i32
+            })
+            .unwrap_or(0);
+        if xp > 0 {
+            self.award_xp(key, xp);
+        }
+        discovered
+    }
+
+    /// Adds `amount` to the `Xp` of `key`, then levels it up as far as its XP allows if it
+    /// has a `Level`, growing its stats and emitting `WorldEvent::LevelUp` for each level.
+    /// Returns the number of levels gained.
+    pub fn award_xp(&self, key: EntityKey, amount: i32) -> u32 {
+        let mut entities = self.entities.borrow_mut();
+        let Some(entity) = entities.get_mut(key) else {
+            return 0;
+        };
+        let xp = match entity.get_mut::<Xp>() {
+            Some(total) => {
+                total.0 += amount;
+                total.0
+            }
+            None => {
+                entity.insert(Xp(amount));
+                amount
+            }
+        };
+
+        let reached = self.progression.curve.level_for(xp);
+        let growth = self.progression.growth;
+        let mut gained = Vec::new();
+        if let Some(level) = entity.get_mut::<Level>() {
+            while level.level < reached {
+                level.level += 1;
+                level.damage += growth.damage;
+                level.defense += growth.defense;
+                gained.push(level.level);
+            }
+        }
+        if let Some(health) = entity.get_mut::<Health>() {
+            health.max += growth.health * gained.len() as i32;
+            health.current += growth.health * gained.len() as i32;
+        }
+        drop(entities);
+
+        for level in gained.iter() {
+            self.emit(WorldEvent::LevelUp {
+                entity: key,
+                level: *level,
+            });
+        }
+        gained.len() as u32
+    }
+
+    /// Syncs the spatial index with the current `Position` of the entity.
+    pub fn reindex(&self, key: EntityKey) {
+        let position = self
+            .entities
+            .borrow()
+            .get(key)
+            .and_then(|entity| entity.get::<Position>().map(|pos| pos.0));
+
+        let mut spatial = self.spatial.borrow_mut();
+        match position {
+            Some(pos) => spatial.insert(key, pos),
+            None => {
+                spatial.remove(key);
+            }
+        }
+    }
+
+    /// Counts down the `StatusEffects` of every entity by one turn. Call it once per turn.
+    ///
+    /// Returns the damage of `Poison` and the healing of `Regeneration`, and emits
+    /// `WorldEvent::StatusExpired` for the effects that ran out.
+    pub fn tick_statuses(&self) -> Vec<Box<dyn Action<T>>> {
+        let mut actions: Vec<Box<dyn Action<T>>> = Vec::new();
+        let mut expired = Vec::new();
+        self.each_mut::<StatusEffects, _>(|key, entity| {
+            let effects = entity.get_mut::<StatusEffects>().unwrap();
+            let (active, ran_out) = effects.tick();
+            for effect in active {
+                match effect.kind {
+                    StatusKind::Poison => {
+                        actions.push(Box::new(DamageAction::new(key, effect.potency)))
+                    }
+                    StatusKind::Regeneration => {
+                        actions.push(Box::new(HealAction::new(key, effect.potency)))
+                    }
+                    _ => {}
+                }
+            }
+            expired.extend(ran_out.into_iter().map(|kind| (key, kind)));
+        });
+        for (entity, kind) in expired {
+            self.emit(WorldEvent::StatusExpired { entity, kind });
+        }
+        actions
+    }
+
+    /// Gives every entity with `Energy` one turn of energy: `ACTION_COST`, changed by `Haste`
+    /// and `Slow`. Call it once per turn.
+    pub fn gain_energy(&self) {
+        self.each_mut::<Energy, _>(|_, entity| {
+            let speed = entity
+                .get::<StatusEffects>()
+                .map_or(ACTION_COST, |effects| effects.speed(ACTION_COST));
+            entity.get_mut::<Energy>().unwrap().0 += speed;
+        });
+    }
+
+    /// Whether the entity has the energy to act. Entities without `Energy` always do.
+    pub fn can_act(&self, key: EntityKey) -> bool {
+        self.entities
+            .borrow()
+            .get(key)
+            .and_then(|entity| entity.get::<Energy>())
+            .is_none_or(|energy| energy.0 >= ACTION_COST)
+    }
+
+    /// Spends the energy of one action.
+    pub fn spend_energy(&self, key: EntityKey) {
+        if let Some(energy) = self
+            .entities
+            .borrow_mut()
+            .get_mut(key)
+            .and_then(|entity| entity.get_mut::<Energy>())
+        {
+            energy.0 -= ACTION_COST;
+        }
+    }
+
+    /// The entities having every component of `Q`.
+    pub fn query<Q: Query>(&self) -> Vec<EntityKey> {
+        self.entities
+            .borrow()
+            .iter()
+            .filter(|(_, entity)| Q::matches(entity))
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    /// Calls `f` with the components of `Q` of every entity having them all.
+    ///
+    /// The entities are borrowed for the whole iteration: `f` must not access
+    /// `World::entities` itself.
+    pub fn each<Q: Query, F: FnMut(EntityKey, Q::Item<'_>)>(&self, mut f: F) {
+        for (key, entity) in self.entities.borrow().iter() {
+            if let Some(item) = Q::fetch(entity) {
+                f(key, item);
+            }
+        }
+    }
+
+    /// Calls `f` with mutable access to every entity having the components of `Q`.
+    ///
+    /// The entities are borrowed for the whole iteration: `f` must not access
+    /// `World::entities` itself.
+    pub fn each_mut<Q: Query, F: FnMut(EntityKey, &mut Entity)>(&self, mut f: F) {
+        for (key, entity) in self.entities.borrow_mut().iter_mut() {
+            if Q::matches(entity) {
+                f(key, entity);
+            }
+        }
+    }
+
+    /// The entities for which `predicate` returns `true`.
+    pub fn filter<F: Fn(EntityKey, &Entity) -> bool>(&self, predicate: F) -> Vec<EntityKey> {
+        self.entities
+            .borrow()
+            .iter()
+            .filter(|(key, entity)| predicate(*key, entity))
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    /// The indexed entities at most `radius` cells away from `center`, in a straight line.
+    /// None when `radius` is negative.
+    pub fn within_radius(&self, center: IntVector2, radius: i32) -> Vec<EntityKey> {
+        if radius < 0 {
+            return Vec::new();
         }
+        let extent = IntExtent2D::new(
+            center.x() - radius,
+            center.y() - radius,
+            (radius * 2 + 1) as usize,
+            (radius * 2 + 1) as usize,
+        );
+        self.spatial
+            .borrow()
+            .in_rect(&extent)
+            .into_iter()
+            .filter(|(_, pos)| {
+                let dx = pos.x() - center.x();
+                let dy = pos.y() - center.y();
+                dx * dx + dy * dy <= radius * radius
+            })
+            .map(|(key, _)| key)
+            .collect()
     }
 
     // pub fn add_entity<F: FnOnce(&mut Entity)>(&self, name: &str, setup_fn: F) -> EntityKey {
@@ -135,6 +135,15 @@ impl<T: Clone> LatticeGrid2D<T> {
         self.data.insert(self.encoder.encode(pos), value);
     }
 
+    /// Like `put`, but returns the value previously stored at `pos`.
+    pub fn replace(&mut self, pos: IntVector2, value: T) -> Option<T> {
+        self.data.insert(self.encoder.encode(pos), value)
+    }
+
+    pub fn remove(&mut self, pos: IntVector2) -> Option<T> {
+        self.data.remove(&self.encoder.encode(pos))
+    }
+
     pub fn at(&self, pos: IntVector2) -> Option<&T> {
         self.data.get(&self.encoder.encode(pos))
     }
@@ -143,7 +152,7 @@ impl<T: Clone> LatticeGrid2D<T> {
         self.data.get_mut(&self.encoder.encode(pos))
     }
 
-    fn neighbors(&self, pos: IntVector2) -> Vec<IntVector2> {
+    pub fn neighbors(&self, pos: IntVector2) -> Vec<IntVector2> {
         let mut neighbors = Vec::new();
         let [x, y] = pos.as_array();
 
@@ -4,6 +4,7 @@ use std::{
     ops::{AddAssign, DivAssign, MulAssign, SubAssign},
 };
 
+mod atlas;
 mod dimension;
 mod direction;
 //mod linearize;
@@ -15,8 +16,11 @@ mod render;
 
 mod scenes;
 mod sprite;
+#[cfg(test)]
+pub(crate) mod test_support;
 mod vector;
 
+pub use atlas::*;
 pub use camera::{Camera, Camera2D, Viewport};
 pub use dimension::*;
 pub use entity::action::*;
@@ -0,0 +1,338 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use serde::Deserialize;
+
+use crate::{IntExtent2D, IntVector2, Map, Tile, TileRegistry, TileSpriteInfo, Vec2};
+
+/// Bits of the 8-neighbour mask, clockwise from north.
+pub const NORTH: u8 = 1;
+pub const NORTH_EAST: u8 = 2;
+pub const EAST: u8 = 4;
+pub const SOUTH_EAST: u8 = 8;
+pub const SOUTH: u8 = 16;
+pub const SOUTH_WEST: u8 = 32;
+pub const WEST: u8 = 64;
+pub const NORTH_WEST: u8 = 128;
+
+const NEIGHBOURS: [(i32, i32, u8); 8] = [
+    (0, -1, NORTH),
+    (1, -1, NORTH_EAST),
+    (1, 0, EAST),
+    (1, 1, SOUTH_EAST),
+    (0, 1, SOUTH),
+    (-1, 1, SOUTH_WEST),
+    (-1, 0, WEST),
+    (-1, -1, NORTH_WEST),
+];
+
+/// How the neighbours of a tile select its sprite variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoTileMode {
+    /// 16 variants chosen by the 4 orthogonal neighbours (north = 1, east = 2, south = 4, west = 8).
+    Cardinal,
+    /// 47 variants chosen by all 8 neighbours. A corner only counts when both sides touching
+    /// it are also set, which leaves 47 distinct masks; variants are indexed by their order.
+    Blob,
+}
+
+impl AutoTileMode {
+    pub fn variants(&self) -> usize {
+        match self {
+            AutoTileMode::Cardinal => 16,
+            AutoTileMode::Blob => 47,
+        }
+    }
+
+    /// The variant index for an 8-neighbour mask.
+    pub fn variant(&self, mask: u8) -> usize {
+        match self {
+            AutoTileMode::Cardinal => cardinal_index(mask),
+            AutoTileMode::Blob => blob_index(mask),
+        }
+    }
+}
+
+fn cardinal_index(mask: u8) -> usize {
+    [NORTH, EAST, SOUTH, WEST]
+        .iter()
+        .enumerate()
+        .filter(|(_, bit)| mask & **bit != 0)
+        .map(|(i, _)| 1 << i)
+        .sum()
+}
+
+/// Clears the corners that are not backed by both adjacent sides.
+fn reduce_corners(mask: u8) -> u8 {
+    let mut reduced = mask & (NORTH | EAST | SOUTH | WEST);
+    for (corner, a, b) in [
+        (NORTH_EAST, NORTH, EAST),
+        (SOUTH_EAST, SOUTH, EAST),
+        (SOUTH_WEST, SOUTH, WEST),
+        (NORTH_WEST, NORTH, WEST),
+    ] {
+        if mask & corner != 0 && mask & a != 0 && mask & b != 0 {
+            reduced |= corner;
+        }
+    }
+    reduced
+}
+
+fn blob_index(mask: u8) -> usize {
+    let reduced = reduce_corners(mask);
+    (0..reduced).filter(|m| reduce_corners(*m) == *m).count()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutoTileError {
+    WrongSpriteCount { mode: AutoTileMode, found: usize },
+}
+
+impl Display for AutoTileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AutoTileError::WrongSpriteCount { mode, found } => write!(
+                f,
+                "{:?} auto-tiling needs {} sprites, got {}",
+                mode,
+                mode.variants(),
+                found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AutoTileError {}
+
+/// The sprite variants of one auto-tiled group, indexed by `AutoTileMode::variant`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoTileRule {
+    pub mode: AutoTileMode,
+    sprites: Vec<String>,
+}
+
+impl AutoTileRule {
+    pub fn new(mode: AutoTileMode, sprites: Vec<String>) -> Result<Self, AutoTileError> {
+        if sprites.len() != mode.variants() {
+            return Err(AutoTileError::WrongSpriteCount {
+                mode,
+                found: sprites.len(),
+            });
+        }
+        Ok(Self { mode, sprites })
+    }
+
+    /// A rule whose variants are the sprites named `<prefix>_0`, `<prefix>_1`, ...
+    pub fn with_prefix(mode: AutoTileMode, prefix: &str) -> Self {
+        Self {
+            mode,
+            sprites: (0..mode.variants())
+                .map(|i| format!("{}_{}", prefix, i))
+                .collect(),
+        }
+    }
+
+    pub fn sprite(&self, mask: u8) -> &str {
+        &self.sprites[self.mode.variant(mask)]
+    }
+}
+
+/// The auto-tile rules of every tile group, keyed by the name used in `TileSpriteInfo::AutoTile`.
+#[derive(Debug, Clone, Default)]
+pub struct AutoTileRules {
+    rules: HashMap<String, AutoTileRule>,
+}
+
+impl AutoTileRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a rule for every definition with an `autotile` mode, using the tile name as
+    /// group and its sprite as prefix of the variant names.
+    pub fn from_registry(registry: &TileRegistry) -> Self {
+        let mut rules = Self::new();
+        for (_, definition) in registry.iter() {
+            if let Some(mode) = definition.autotile {
+                rules.add(
+                    &definition.name,
+                    AutoTileRule::with_prefix(mode, &definition.sprite),
+                );
+            }
+        }
+        rules
+    }
+
+    pub fn add(&mut self, group: &str, rule: AutoTileRule) {
+        self.rules.insert(group.to_owned(), rule);
+    }
+
+    pub fn get(&self, group: &str) -> Option<&AutoTileRule> {
+        self.rules.get(group)
+    }
+}
+
+fn group_of<T: Tile>(tile: &T) -> Option<&'static str> {
+    match tile.sprite_info() {
+        TileSpriteInfo::AutoTile(group) => Some(group),
+        _ => None,
+    }
+}
+
+/// The 8-neighbour mask of the cell at `pos`: a bit is set when that neighbour is in the
+/// same auto-tile group. Returns `None` if the cell is not auto-tiled.
+pub fn neighbour_mask<T: Tile>(map: &Map<T>, pos: IntVector2) -> Option<u8> {
+    let group = group_of(map.get(pos.x(), pos.y())?)?;
+    Some(
+        NEIGHBOURS
+            .iter()
+            .filter(|(dx, dy, _)| {
+                map.get(pos.x() + dx, pos.y() + dy)
+                    .and_then(group_of)
+                    .is_some_and(|other| other == group)
+            })
+            .fold(0, |mask, (_, _, bit)| mask | bit),
+    )
+}
+
+/// The sprite chosen for every auto-tiled cell of a map.
+///
+/// Build it once with `rebuild`, then keep it current with `update` using the dirty region
+/// reported by `Map::take_dirty_region`.
+#[derive(Debug, Clone, Default)]
+pub struct AutoTileCache {
+    sprites: HashMap<IntVector2, String>,
+}
+
+impl AutoTileCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rebuild<T: Tile>(&mut self, map: &Map<T>, rules: &AutoTileRules) {
+        self.sprites.clear();
+        self.update(map, rules, &map.size());
+    }
+
+    /// Recomputes the cells in `region` and the ring of cells around it, whose masks
+    /// depend on the cells inside.
+    pub fn update<T: Tile>(&mut self, map: &Map<T>, rules: &AutoTileRules, region: &IntExtent2D) {
+        let grown = IntExtent2D::new(
+            region.left() - 1,
+            region.top() - 1,
+            region.width() + 2,
+            region.height() + 2,
+        );
+        for pos in grown.iter() {
+            let sprite = map
+                .get(pos.x(), pos.y())
+                .and_then(group_of)
+                .and_then(|group| rules.get(group))
+                .zip(neighbour_mask(map, pos))
+                .map(|(rule, mask)| rule.sprite(mask).to_owned());
+            match sprite {
+                Some(sprite) => {
+                    self.sprites.insert(pos, sprite);
+                }
+                None => {
+                    self.sprites.remove(&pos);
+                }
+            }
+        }
+    }
+
+    pub fn sprite(&self, x: i32, y: i32) -> Option<&str> {
+        self.sprites.get(&IntVector2::new(x, y)).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dimension2D, FovOccluder, ItemContainer, Visible, Visited, Walkable};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestTile {
+        wall: bool,
+    }
+    impl Tile for TestTile {
+        fn sprite_info(&self) -> TileSpriteInfo {
+            if self.wall {
+                TileSpriteInfo::AutoTile("wall")
+            } else {
+                TileSpriteInfo::SpriteSheet("floor")
+            }
+        }
+    }
+    impl Visible for TestTile {}
+    impl Visited for TestTile {}
+    impl FovOccluder for TestTile {}
+    impl Walkable for TestTile {}
+    impl ItemContainer for TestTile {}
+
+    fn map_from(rows: &[&str]) -> Map<TestTile> {
+        let mut map = Map::new(
+            IntExtent2D::new(0, 0, rows[0].len(), rows.len()),
+            Dimension2D::new(1, 1),
+        );
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                map.set(x as i32, y as i32, TestTile { wall: c == '#' });
+            }
+        }
+        map
+    }
+
+    #[test]
+    fn test_variant_indices() {
+        assert_eq!(AutoTileMode::Cardinal.variant(0), 0);
+        assert_eq!(AutoTileMode::Cardinal.variant(NORTH | WEST | NORTH_WEST), 9);
+        assert_eq!(AutoTileMode::Blob.variant(0), 0);
+        assert_eq!(AutoTileMode::Blob.variant(0xff), 46);
+        // a lone corner is ignored
+        assert_eq!(
+            AutoTileMode::Blob.variant(NORTH_EAST),
+            AutoTileMode::Blob.variant(0)
+        );
+        let distinct = (0..=255u8)
+            .map(|m| AutoTileMode::Blob.variant(m))
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(distinct.len(), 47);
+    }
+
+    #[test]
+    fn test_cache_follows_map_changes() {
+        let mut map = map_from(&["###", "#..", "#.."]);
+        let mut rules = AutoTileRules::new();
+        rules.add(
+            "wall",
+            AutoTileRule::with_prefix(AutoTileMode::Cardinal, "wall"),
+        );
+        assert!(AutoTileRule::new(AutoTileMode::Blob, vec![]).is_err());
+
+        let mut cache = AutoTileCache::new();
+        cache.rebuild(&map, &rules);
+        assert_eq!(cache.len(), 5);
+        assert_eq!(cache.sprite(0, 0), Some("wall_6"));
+        assert_eq!(cache.sprite(2, 0), Some("wall_8"));
+        assert_eq!(cache.sprite(1, 1), None);
+
+        map.take_dirty_region();
+        map.set(1, 1, TestTile { wall: true });
+        let dirty = map.take_dirty_region().unwrap();
+        cache.update(&map, &rules, &dirty);
+        assert_eq!(cache.sprite(1, 0), Some("wall_14"));
+        assert_eq!(cache.sprite(1, 1), Some("wall_9"));
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use crate::{IntVector2, Map, MapCommand, MapCommands, Tile, Vec2};
+
+/// A set of edits staged against a `Map` and applied together when the batch is dropped.
+///
+/// Tiles are written first, then the queued commands are processed, so a command
+/// can target a tile placed in the same batch.
+pub struct MapBatch<'a, T: Tile> {
+    map: &'a mut Map<T>,
+    tiles: HashMap<IntVector2, T>,
+    commands: MapCommands<T>,
+}
+
+impl<'a, T: Tile> MapBatch<'a, T> {
+    pub(super) fn new(map: &'a mut Map<T>) -> Self {
+        Self {
+            map,
+            tiles: HashMap::new(),
+            commands: MapCommands::default(),
+        }
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, tile: T) -> &mut Self {
+        self.tiles.insert(IntVector2::new(x, y), tile);
+        self
+    }
+
+    pub fn add_command(&mut self, command: MapCommand<T>) -> &mut Self {
+        self.commands.add(command);
+        self
+    }
+
+    /// Returns the tile at `(x, y)`, including the edits staged in this batch.
+    pub fn get(&self, x: i32, y: i32) -> Option<&T> {
+        self.tiles
+            .get(&IntVector2::new(x, y))
+            .or_else(|| self.map.get(x, y))
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len() + self.commands.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty() && self.commands.is_empty()
+    }
+
+    /// Drops every staged edit, leaving the map untouched.
+    pub fn discard(mut self) {
+        self.tiles.clear();
+        self.commands.clear();
+    }
+}
+
+impl<T: Tile> Drop for MapBatch<'_, T> {
+    fn drop(&mut self) {
+        for (pos, tile) in self.tiles.drain() {
+            self.map.set(pos.x(), pos.y(), tile);
+        }
+        self.commands.process_commands(self.map);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::VisibleTile, Dimension2D, IntExtent2D};
+
+    fn map() -> Map<VisibleTile> {
+        Map::new(IntExtent2D::new(0, 0, 4, 4), Dimension2D::new(8, 8))
+    }
+
+    #[test]
+    fn test_batch_applies_on_drop() {
+        let mut map = map();
+        {
+            let mut batch = map.batch();
+            batch
+                .set(1, 1, VisibleTile::default())
+                .add_command(MapCommand::SetVisible(IntVector2::new(1, 1), true));
+            assert!(batch.get(1, 1).is_some());
+            assert_eq!(batch.len(), 2);
+        }
+        assert_eq!(map.get(1, 1), Some(&VisibleTile { visible: true }));
+    }
+
+    #[test]
+    fn test_batch_discard() {
+        let mut map = map();
+        let mut batch = map.batch();
+        batch.set(2, 2, VisibleTile::default());
+        batch.discard();
+        assert!(map.is_empty());
+    }
+}
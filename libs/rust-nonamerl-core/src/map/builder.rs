@@ -1,6 +1,13 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
 
-use crate::{Dimension2D, IntExtent2D, IntVector2, Map, Room, Tile};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    Dimension2D, FromTileDefinition, IntExtent2D, IntVector2, Map, Room, Tile, TileRegistry,
+};
 
 /// A trait for defining algorithms that can be used to build maps.
 pub trait MapBuilderAlgorithm<T: Tile> {
@@ -12,10 +19,30 @@ pub trait MapBuilderAlgorithm<T: Tile> {
     ///
     /// # Returns
     ///
-    /// A mutable reference to the modified `MapBuilder` instance.
-    fn build<'a>(&self, map_builder: &'a mut MapBuilder<T>) -> &'a mut MapBuilder<T>;
+    /// A mutable reference to the modified `MapBuilder` instance, or an error if the
+    /// algorithm needs a tile that has not been added to the builder.
+    fn build<'a>(
+        &self,
+        map_builder: &'a mut MapBuilder<T>,
+    ) -> Result<&'a mut MapBuilder<T>, MapBuilderError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapBuilderError {
+    /// A builder step asked for a tile name that was never added.
+    UndefinedTile(String),
 }
 
+impl Display for MapBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapBuilderError::UndefinedTile(name) => write!(f, "undefined tile `{}`", name),
+        }
+    }
+}
+
+impl std::error::Error for MapBuilderError {}
+
 #[derive(Clone, Debug)]
 pub struct MapBuilder<T: Tile> {
     /// The extent of the map
@@ -23,14 +50,26 @@ pub struct MapBuilder<T: Tile> {
     pub rooms: Vec<Room>,
     /// The different types of tiles that can be used to build the map.
     pub(super) tiles: HashMap<String, T>,
+    /// The source of every random choice made by the steps, seed it for reproducible maps.
+    pub rng: StdRng,
 }
 
 impl<T: Tile> MapBuilder<T> {
     pub fn new(extent: IntExtent2D, cell_size: Dimension2D<usize>) -> Self {
+        Self::with_rng(extent, cell_size, StdRng::from_entropy())
+    }
+
+    /// A builder whose steps build the same map on every run.
+    pub fn with_seed(extent: IntExtent2D, cell_size: Dimension2D<usize>, seed: u64) -> Self {
+        Self::with_rng(extent, cell_size, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(extent: IntExtent2D, cell_size: Dimension2D<usize>, rng: StdRng) -> Self {
         Self {
             map: Map::new(extent, cell_size),
             tiles: HashMap::new(),
             rooms: Vec::new(),
+            rng,
         }
     }
 
@@ -38,6 +77,24 @@ impl<T: Tile> MapBuilder<T> {
         self.tiles.insert(name.to_string(), tile);
     }
 
+    /// Adds a tile for every definition in the registry, keyed by the definition name.
+    pub fn add_tiles_from_registry(&mut self, registry: &TileRegistry)
+    where
+        T: FromTileDefinition,
+    {
+        for (id, definition) in registry.iter() {
+            self.add_tile(&definition.name, T::from_definition(id, definition));
+        }
+    }
+
+    /// Returns a copy of the tile added with the given name.
+    pub fn tile(&self, name: &str) -> Result<T, MapBuilderError> {
+        self.tiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| MapBuilderError::UndefinedTile(name.to_string()))
+    }
+
     /// Adds a step to the map building process using the given `MapBuilderAlgorithm`.
     ///
     /// # Arguments
@@ -46,10 +103,12 @@ impl<T: Tile> MapBuilder<T> {
     ///
     /// # Returns
     ///
-    /// A mutable reference to the modified `MapBuilder` instance.
-    pub fn add_step<'a>(&'a mut self, algorithm: &'a impl MapBuilderAlgorithm<T>) -> &'a mut Self {
-        let builder = algorithm.build(self);
-        builder
+    /// A mutable reference to the modified `MapBuilder` instance, so that steps can be chained.
+    pub fn add_step(
+        &mut self,
+        algorithm: &impl MapBuilderAlgorithm<T>,
+    ) -> Result<&mut Self, MapBuilderError> {
+        algorithm.build(self)
     }
 
     // Builds a `Map` instance using the current state of the `MapBuilder`.
@@ -75,16 +134,18 @@ impl<T: Clone> FillWithFloorBuilderAlgo<T> {
 }
 
 impl<T: Tile> MapBuilderAlgorithm<T> for FillWithFloorBuilderAlgo<T> {
-    fn build<'a>(&self, map_builder: &'a mut MapBuilder<T>) -> &'a mut MapBuilder<T> {
+    fn build<'a>(
+        &self,
+        map_builder: &'a mut MapBuilder<T>,
+    ) -> Result<&'a mut MapBuilder<T>, MapBuilderError> {
+        let grass = map_builder.tile("grass")?;
         for x in map_builder.map.extent.left()..map_builder.map.extent.right() {
             for y in map_builder.map.extent.top()..map_builder.map.extent.bottom() {
-                map_builder
-                    .map
-                    .set(x, y, map_builder.tiles["grass"].clone());
+                map_builder.map.set(x, y, grass.clone());
             }
         }
 
-        map_builder
+        Ok(map_builder)
     }
 }
 
@@ -92,7 +153,7 @@ impl<T: Tile> MapBuilderAlgorithm<T> for FillWithFloorBuilderAlgo<T> {
 
 mod tests {
 
-    use crate::{FovOccluder, ItemContainer, Visible, Visited, Walkable};
+    use crate::{FovOccluder, ItemContainer, TileDefinition, TileId, Visible, Visited, Walkable};
 
     use super::*;
 
@@ -104,6 +165,11 @@ mod tests {
     impl FovOccluder for TestTile {}
     impl Walkable for TestTile {}
     impl ItemContainer for TestTile {}
+    impl FromTileDefinition for TestTile {
+        fn from_definition(_id: TileId, _definition: &TileDefinition) -> Self {
+            TestTile {}
+        }
+    }
 
     #[test]
     fn test_map_builder() {
@@ -122,10 +188,39 @@ mod tests {
             MapBuilder::<TestTile>::new(IntExtent2D::new(0, 0, 10, 10), Dimension2D::new(24, 24));
         map_builder.add_tile("grass", TestTile {});
         map_builder.add_tile("water", TestTile {});
-        map_builder.add_step(&FillWithFloorBuilderAlgo::<TestTile>::new());
+        map_builder
+            .add_step(&FillWithFloorBuilderAlgo::<TestTile>::new())
+            .unwrap();
 
         assert_eq!(map_builder.map.len(), 100);
 
         // assert_eq!(map_builder.map.grid, 100);
     }
+
+    #[test]
+    fn test_map_builder_undefined_tile() {
+        let mut map_builder =
+            MapBuilder::<TestTile>::new(IntExtent2D::new(0, 0, 10, 10), Dimension2D::new(24, 24));
+
+        let result = map_builder.add_step(&FillWithFloorBuilderAlgo::<TestTile>::new());
+
+        assert_eq!(
+            result.err(),
+            Some(MapBuilderError::UndefinedTile("grass".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_map_builder_tiles_from_registry() {
+        let registry = TileRegistry::from_json(
+            r#"{ "tiles": [ { "name": "grass", "sprite": "grass", "walkable": true } ] }"#,
+        )
+        .unwrap();
+        let mut map_builder =
+            MapBuilder::<TestTile>::new(IntExtent2D::new(0, 0, 10, 10), Dimension2D::new(24, 24));
+
+        map_builder.add_tiles_from_registry(&registry);
+
+        assert_eq!(map_builder.tile("grass"), Ok(TestTile {}));
+    }
 }
@@ -1,29 +1,117 @@
-use crate::{world::ItemKey, IntVector2, Map, Tile, Vec2};
+use crate::{world::ItemKey, IntVector2, Map, Tile, TileTransition, Vec2};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum MapCommand {
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapCommand<T: Tile> {
     SetVisited(IntVector2, bool),
     SetVisible(IntVector2, bool),
+    /// Marks a tile as hidden from sight, see `FovOccluder::is_obscured`.
+    SetObscured(IntVector2, bool),
     AddItem(IntVector2, ItemKey),
+    RemoveItem(IntVector2, ItemKey),
+    MoveItem {
+        from: IntVector2,
+        to: IntVector2,
+        item: ItemKey,
+    },
+    /// Places a tile, replacing whatever was in the cell.
+    SetTile(IntVector2, T),
+    RemoveTile(IntVector2),
+    /// Removes durability from a tile; at 0 the tile goes through `TileTransition::Destroyed`,
+    /// or is removed if it defines no such transition.
+    DamageTile(IntVector2, u32),
+    TransformTile(IntVector2, TileTransition),
+}
+
+impl<T: Tile> MapCommand<T> {
+    /// Applies the command and returns the command that reverts it.
+    ///
+    /// Returns `None` when the command had nothing to act on (an empty cell, a missing item),
+    /// in which case the map is left untouched.
+    pub fn apply(self, map: &mut Map<T>) -> Option<MapCommand<T>> {
+        match self {
+            MapCommand::SetVisited(pos, visited) => {
+                let previous = map.get(pos.x(), pos.y())?.is_visited();
+                map.set_visited(pos.x(), pos.y(), visited);
+                Some(MapCommand::SetVisited(pos, previous))
+            }
+            MapCommand::SetVisible(pos, visible) => {
+                let previous = map.get(pos.x(), pos.y())?.is_visible();
+                map.set_visible(pos.x(), pos.y(), visible);
+                Some(MapCommand::SetVisible(pos, previous))
+            }
+            MapCommand::SetObscured(pos, obscured) => {
+                let previous = map.get(pos.x(), pos.y())?.is_obscured();
+                map.set_obscured(pos.x(), pos.y(), obscured);
+                Some(MapCommand::SetObscured(pos, previous))
+            }
+            MapCommand::AddItem(pos, item) => map
+                .add_item(pos.x(), pos.y(), item)
+                .then_some(MapCommand::RemoveItem(pos, item)),
+            MapCommand::RemoveItem(pos, item) => map
+                .remove_item(pos.x(), pos.y(), item)
+                .then_some(MapCommand::AddItem(pos, item)),
+            MapCommand::MoveItem { from, to, item } => {
+                map.get(to.x(), to.y())?;
+                if !map.remove_item(from.x(), from.y(), item) {
+                    return None;
+                }
+                map.add_item(to.x(), to.y(), item);
+                Some(MapCommand::MoveItem {
+                    from: to,
+                    to: from,
+                    item,
+                })
+            }
+            MapCommand::SetTile(pos, tile) => Some(match map.replace(pos.x(), pos.y(), tile) {
+                Some(previous) => MapCommand::SetTile(pos, previous),
+                None => MapCommand::RemoveTile(pos),
+            }),
+            MapCommand::DamageTile(pos, amount) => {
+                let previous = map.get(pos.x(), pos.y())?.clone();
+                let durability = previous.durability()?;
+                if durability > amount {
+                    let mut tile = previous.clone();
+                    tile.set_durability(durability - amount);
+                    map.set(pos.x(), pos.y(), tile);
+                } else {
+                    match previous.transition(TileTransition::Destroyed) {
+                        Some(tile) => map.set(pos.x(), pos.y(), tile),
+                        None => {
+                            map.remove(pos.x(), pos.y());
+                        }
+                    }
+                }
+                Some(MapCommand::SetTile(pos, previous))
+            }
+            MapCommand::TransformTile(pos, transition) => {
+                let tile = map.get(pos.x(), pos.y())?.transition(transition)?;
+                map.replace(pos.x(), pos.y(), tile)
+                    .map(|previous| MapCommand::SetTile(pos, previous))
+            }
+            MapCommand::RemoveTile(pos) => map
+                .remove(pos.x(), pos.y())
+                .map(|previous| MapCommand::SetTile(pos, previous)),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
-pub struct MapCommands {
-    pub commands: Vec<MapCommand>,
+pub struct MapCommands<T: Tile> {
+    pub commands: Vec<MapCommand<T>>,
 }
 
-impl MapCommands {
+impl<T: Tile> MapCommands<T> {
     fn new() -> Self {
         Self {
             commands: Vec::new(),
         }
     }
 
-    pub fn add(&mut self, command: MapCommand) {
+    pub fn add(&mut self, command: MapCommand<T>) {
         self.commands.push(command);
     }
 
-    pub fn add_all(&mut self, commands: Vec<MapCommand>) {
+    pub fn add_all(&mut self, commands: Vec<MapCommand<T>>) {
         self.commands.extend(commands);
     }
 
@@ -35,25 +123,19 @@ impl MapCommands {
         self.commands.clear();
     }
 
-    pub fn process_commands<T: Tile>(&mut self, map: &mut Map<T>) {
-        for command in self.commands.iter() {
-            match command {
-                MapCommand::SetVisited(pos, visited) => {
-                    map.set_visited(pos.x(), pos.y(), *visited);
-                }
-                MapCommand::SetVisible(pos, visible) => {
-                    map.set_visible(pos.x(), pos.y(), *visible);
-                }
-                MapCommand::AddItem(pos, item) => {
-                    map.add_item(pos.x(), pos.y(), *item);
-                }
-            }
-        }
-        self.commands.clear();
+    /// Applies the queued commands in order, returning their inverses in undo order.
+    pub fn process_commands(&mut self, map: &mut Map<T>) -> Vec<MapCommand<T>> {
+        let mut inverses = self
+            .commands
+            .drain(..)
+            .filter_map(|command| command.apply(map))
+            .collect::<Vec<_>>();
+        inverses.reverse();
+        inverses
     }
 }
 
-impl Default for MapCommands {
+impl<T: Tile> Default for MapCommands<T> {
     fn default() -> Self {
         Self::new()
     }
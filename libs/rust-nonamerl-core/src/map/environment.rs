@@ -0,0 +1,443 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    world::World, Action, DamageAction, IntVector2, Map, MapCommand, Tile, TileTransition, Vec2,
+};
+
+/// A tile that can catch fire. Once the fire burns out, the tile goes through
+/// `TileTransition::Burnt`.
+pub trait Flammable {
+    /// Chance, from 0 to 1, that fire on a neighbouring cell spreads to this tile each turn.
+    fn flammability(&self) -> f32 {
+        0.
+    }
+}
+
+/// The hazards present on one cell.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EnvironmentCell {
+    /// Turns the fire on this cell keeps burning, 0 when not on fire.
+    pub fire: u32,
+    /// Gas density, from 0 to 1.
+    pub gas: f32,
+    /// Liquid depth.
+    pub liquid: f32,
+}
+
+impl EnvironmentCell {
+    fn is_empty(&self) -> bool {
+        self.fire == 0 && self.gas <= f32::EPSILON && self.liquid <= f32::EPSILON
+    }
+}
+
+/// Tuning values of the simulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnvironmentConfig {
+    /// Turns a tile burns once ignited.
+    pub burn_turns: u32,
+    /// Fraction of a cell's gas shared with its open neighbours each turn.
+    pub gas_diffusion: f32,
+    /// Gas lost each turn, as a fraction of the density.
+    pub gas_decay: f32,
+    /// Density from which gas blocks line of sight.
+    pub gas_opacity: f32,
+    /// Fraction of the depth difference that flows to a lower neighbour each turn.
+    pub liquid_flow: f32,
+    pub fire_damage: i32,
+    /// Damage dealt by gas at full density.
+    pub gas_damage: i32,
+}
+
+impl Default for EnvironmentConfig {
+    fn default() -> Self {
+        Self {
+            burn_turns: 3,
+            gas_diffusion: 0.5,
+            gas_decay: 0.05,
+            gas_opacity: 0.5,
+            liquid_flow: 0.25,
+            fire_damage: 5,
+            gas_damage: 0,
+        }
+    }
+}
+
+/// A cellular simulation of fire, gas and liquids laid over a `Map`.
+///
+/// Call `step` once per game turn. It never modifies the map directly: tiles changed by
+/// the simulation (e.g. burnt grass) and cells whose gas starts or stops blocking sight
+/// are returned as `MapCommand`s.
+#[derive(Debug, Clone)]
+pub struct Environment {
+    cells: HashMap<IntVector2, EnvironmentCell>,
+    /// The cells last marked obscured on the map.
+    obscured: HashSet<IntVector2>,
+    pub config: EnvironmentConfig,
+    rng: StdRng,
+}
+
+impl Environment {
+    pub fn new(seed: u64) -> Self {
+        Self::with_config(seed, EnvironmentConfig::default())
+    }
+
+    pub fn with_config(seed: u64, config: EnvironmentConfig) -> Self {
+        Self {
+            cells: HashMap::new(),
+            obscured: HashSet::new(),
+            config,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn get(&self, pos: IntVector2) -> EnvironmentCell {
+        self.cells.get(&pos).copied().unwrap_or_default()
+    }
+
+    /// Sets fire to the tile at `pos`. Returns `false` if the tile cannot burn.
+    pub fn ignite<T: Tile + Flammable>(&mut self, map: &Map<T>, pos: IntVector2) -> bool {
+        match map.get(pos.x(), pos.y()) {
+            Some(tile) if tile.flammability() > 0. => {
+                self.cells.entry(pos).or_default().fire = self.config.burn_turns;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn add_gas(&mut self, pos: IntVector2, amount: f32) {
+        let cell = self.cells.entry(pos).or_default();
+        cell.gas = (cell.gas + amount).min(1.);
+    }
+
+    pub fn add_liquid(&mut self, pos: IntVector2, amount: f32) {
+        self.cells.entry(pos).or_default().liquid += amount;
+    }
+
+    pub fn is_burning(&self, pos: IntVector2) -> bool {
+        self.get(pos).fire > 0
+    }
+
+    /// Whether the gas on `pos` is thick enough to stop line of sight.
+    pub fn blocks_vision(&self, pos: IntVector2) -> bool {
+        self.get(pos).gas >= self.config.gas_opacity
+    }
+
+    /// Damage taken by an entity standing on `pos` for one turn.
+    pub fn damage_at(&self, pos: IntVector2) -> i32 {
+        let cell = self.get(pos);
+        let fire = if cell.fire > 0 {
+            self.config.fire_damage
+        } else {
+            0
+        };
+        fire + (cell.gas * self.config.gas_damage as f32).round() as i32
+    }
+
+    /// The damage taken by every indexed entity standing on a hazardous cell.
+    pub fn damage_actions<T: Tile>(&self, world: &World<T>) -> Vec<Box<dyn Action<T>>> {
+        let spatial = world.spatial.borrow();
+        let mut hazards = self
+            .cells
+            .keys()
+            .map(|pos| (*pos, self.damage_at(*pos)))
+            .filter(|(_, damage)| *damage > 0)
+            .collect::<Vec<_>>();
+        // hurt in a stable order so the same turn always plays out the same way
+        hazards.sort_by_key(|(pos, _)| (pos.y(), pos.x()));
+        hazards
+            .into_iter()
+            .flat_map(|(pos, damage)| {
+                spatial.at(pos).iter().map(move |entity| {
+                    Box::new(DamageAction::new(*entity, damage)) as Box<dyn Action<T>>
+                })
+            })
+            .collect()
+    }
+
+    /// Advances the simulation by one turn.
+    pub fn step<T: Tile + Flammable>(&mut self, map: &Map<T>) -> Vec<MapCommand<T>> {
+        let mut commands = self.step_fire(map);
+        self.step_gas(map);
+        self.step_liquid(map);
+        self.cells.retain(|_, cell| !cell.is_empty());
+        commands.extend(self.obscure_commands());
+        commands
+    }
+
+    /// `MapCommand::SetObscured` for the cells whose gas started or stopped blocking
+    /// sight since the last call.
+    fn obscure_commands<T: Tile>(&mut self) -> Vec<MapCommand<T>> {
+        let obscured = self
+            .cells
+            .keys()
+            .filter(|pos| self.blocks_vision(**pos))
+            .copied()
+            .collect::<HashSet<_>>();
+        let mut changed = obscured
+            .difference(&self.obscured)
+            .map(|pos| (*pos, true))
+            .chain(self.obscured.difference(&obscured).map(|pos| (*pos, false)))
+            .collect::<Vec<_>>();
+        changed.sort_by_key(|(pos, _)| (pos.y(), pos.x()));
+        self.obscured = obscured;
+        changed
+            .into_iter()
+            .map(|(pos, obscured)| MapCommand::SetObscured(pos, obscured))
+            .collect()
+    }
+
+    fn step_fire<T: Tile + Flammable>(&mut self, map: &Map<T>) -> Vec<MapCommand<T>> {
+        let mut burning = self
+            .cells
+            .iter()
+            .filter(|(_, cell)| cell.fire > 0)
+            .map(|(pos, _)| *pos)
+            .collect::<Vec<_>>();
+        // spread in a stable order so a seed always gives the same fire
+        burning.sort_by_key(|pos| (pos.y(), pos.x()));
+
+        let mut ignited = Vec::new();
+        for pos in burning.iter() {
+            for next in map.neighbors(*pos) {
+                if self.is_burning(next) || ignited.contains(&next) {
+                    continue;
+                }
+                if let Some(tile) = map.get(next.x(), next.y()) {
+                    if self.rng.gen::<f32>() < tile.flammability() {
+                        ignited.push(next);
+                    }
+                }
+            }
+        }
+
+        let mut commands = Vec::new();
+        for pos in burning {
+            let cell = self.cells.get_mut(&pos).unwrap();
+            cell.fire -= 1;
+            if cell.fire == 0 {
+                let burns = map
+                    .get(pos.x(), pos.y())
+                    .is_some_and(|tile| tile.transition(TileTransition::Burnt).is_some());
+                if burns {
+                    commands.push(MapCommand::TransformTile(pos, TileTransition::Burnt));
+                }
+            }
+        }
+        for pos in ignited {
+            self.cells.entry(pos).or_default().fire = self.config.burn_turns;
+        }
+        commands
+    }
+
+    fn step_gas<T: Tile>(&mut self, map: &Map<T>) {
+        let mut gas = HashMap::<IntVector2, f32>::new();
+        for (pos, cell) in self.cells.iter().filter(|(_, cell)| cell.gas > 0.) {
+            let open = map
+                .neighbors(*pos)
+                .into_iter()
+                .filter(|n| map.is_walkable(n.x(), n.y()))
+                .collect::<Vec<_>>();
+            let shared = if open.is_empty() {
+                0.
+            } else {
+                cell.gas * self.config.gas_diffusion
+            };
+            *gas.entry(*pos).or_default() += cell.gas - shared;
+            for n in open.iter() {
+                *gas.entry(*n).or_default() += shared / open.len() as f32;
+            }
+        }
+
+        self.cells.values_mut().for_each(|cell| cell.gas = 0.);
+        for (pos, density) in gas {
+            let density = density * (1. - self.config.gas_decay);
+            if density > 0.01 {
+                self.cells.entry(pos).or_default().gas = density.min(1.);
+            }
+        }
+    }
+
+    fn step_liquid<T: Tile>(&mut self, map: &Map<T>) {
+        let mut flows = Vec::new();
+        for (pos, cell) in self.cells.iter().filter(|(_, cell)| cell.liquid > 0.) {
+            for n in map.neighbors(*pos) {
+                if !map.is_walkable(n.x(), n.y()) {
+                    continue;
+                }
+                let difference = cell.liquid - self.get(n).liquid;
+                if difference > 0. {
+                    // each cell has 4 neighbours, so it never gives away more than it has
+                    flows.push((*pos, n, difference * self.config.liquid_flow / 4.));
+                }
+            }
+        }
+        for (from, to, amount) in flows {
+            self.cells.entry(from).or_default().liquid -= amount;
+            self.cells.entry(to).or_default().liquid += amount;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (IntVector2, &EnvironmentCell)> {
+        self.cells.iter().map(|(pos, cell)| (*pos, cell))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compute_fov, compute_fov_with, Dimension2D, FovOccluder, IntExtent2D, ItemContainer,
+        MapEvent, Visible, Visited, Walkable,
+    };
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum TestTile {
+        Grass,
+        Ash,
+        Floor,
+        Smoky,
+    }
+    impl Tile for TestTile {
+        fn transition(&self, transition: TileTransition) -> Option<Self> {
+            match (self, transition) {
+                (TestTile::Grass, TileTransition::Burnt) => Some(TestTile::Ash),
+                _ => None,
+            }
+        }
+    }
+    impl Visible for TestTile {}
+    impl Visited for TestTile {}
+    impl FovOccluder for TestTile {
+        fn is_obscured(&self) -> bool {
+            *self == TestTile::Smoky
+        }
+        fn set_obscured(&mut self, obscured: bool) {
+            *self = if obscured {
+                TestTile::Smoky
+            } else {
+                TestTile::Floor
+            };
+        }
+    }
+    impl Walkable for TestTile {}
+    impl ItemContainer for TestTile {}
+    impl Flammable for TestTile {
+        fn flammability(&self) -> f32 {
+            match self {
+                TestTile::Grass => 1.,
+                _ => 0.,
+            }
+        }
+    }
+
+    fn map(width: usize, tile: TestTile) -> Map<TestTile> {
+        let mut map = Map::new(IntExtent2D::new(0, 0, width, 1), Dimension2D::new(1, 1));
+        for x in 0..width as i32 {
+            map.set(x, 0, tile.clone());
+        }
+        map
+    }
+
+    #[test]
+    fn test_fire_spreads_and_burns_out() {
+        let mut map = map(4, TestTile::Grass);
+        map.set(3, 0, TestTile::Floor);
+        let mut environment = Environment::new(0);
+        assert!(environment.ignite(&map, IntVector2::new(0, 0)));
+        assert!(!environment.ignite(&map, IntVector2::new(3, 0)));
+
+        let mut burnt = Vec::new();
+        for _ in 0..10 {
+            for command in environment.step(&map) {
+                if let MapCommand::TransformTile(pos, _) = &command {
+                    burnt.push(pos.x());
+                }
+                command.apply(&mut map);
+            }
+        }
+        burnt.sort();
+        assert_eq!(burnt, vec![0, 1, 2]);
+        assert_eq!(map.get(3, 0), Some(&TestTile::Floor));
+        assert!(environment.is_empty());
+    }
+
+    #[test]
+    fn test_gas_diffuses_and_blocks_vision() {
+        let map = map(7, TestTile::Floor);
+        let mut environment = Environment::new(0);
+        environment.add_gas(IntVector2::new(3, 0), 1.);
+        assert!(environment.blocks_vision(IntVector2::new(3, 0)));
+
+        let fov = compute_fov_with(&map, IntVector2::new(0, 0), 6, |pos| {
+            environment.blocks_vision(pos)
+        });
+        assert!(fov.contains(&IntVector2::new(3, 0)));
+        assert!(!fov.contains(&IntVector2::new(4, 0)));
+
+        environment.step(&map);
+        let left = environment.get(IntVector2::new(2, 0)).gas;
+        let right = environment.get(IntVector2::new(4, 0)).gas;
+        assert!(left > 0. && (left - right).abs() < f32::EPSILON);
+        assert!(environment.get(IntVector2::new(3, 0)).gas < 1.);
+    }
+
+    #[test]
+    fn test_gas_obscures_tiles() {
+        let mut map = map(7, TestTile::Floor);
+        let mut environment = Environment::with_config(
+            0,
+            EnvironmentConfig {
+                gas_diffusion: 0.1,
+                ..Default::default()
+            },
+        );
+        environment.add_gas(IntVector2::new(3, 0), 1.);
+        let commands = environment.step(&map);
+        assert_eq!(
+            commands,
+            vec![MapCommand::SetObscured(IntVector2::new(3, 0), true)]
+        );
+        let subscriber = map.subscribe();
+        commands.into_iter().for_each(|command| {
+            command.apply(&mut map);
+        });
+        assert_eq!(
+            map.poll_events(subscriber),
+            vec![MapEvent::ObscuredChanged(IntVector2::new(3, 0), true)]
+        );
+        assert!(map.take_dirty_region().is_some());
+
+        // the plain FOV sees the gas through the obscured tile
+        let fov = compute_fov(&map, IntVector2::new(0, 0), 6);
+        assert!(fov.contains(&IntVector2::new(3, 0)));
+        assert!(!fov.contains(&IntVector2::new(4, 0)));
+
+        for _ in 0..20 {
+            for command in environment.step(&map) {
+                command.apply(&mut map);
+            }
+        }
+        assert_eq!(map.get(3, 0), Some(&TestTile::Floor));
+        assert!(compute_fov(&map, IntVector2::new(0, 0), 6).contains(&IntVector2::new(6, 0)));
+    }
+
+    #[test]
+    fn test_liquid_levels_out() {
+        let map = map(3, TestTile::Floor);
+        let mut environment = Environment::new(0);
+        environment.add_liquid(IntVector2::new(0, 0), 3.);
+        for _ in 0..200 {
+            environment.step(&map);
+        }
+        let total: f32 = environment.iter().map(|(_, cell)| cell.liquid).sum();
+        assert!((total - 3.).abs() < 0.001);
+        assert!((environment.get(IntVector2::new(2, 0)).liquid - 1.).abs() < 0.05);
+    }
+}
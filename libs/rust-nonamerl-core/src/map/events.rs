@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use crate::{world::ItemKey, IntExtent2D, IntVector2, Vec2};
+
+/// A change to a single map cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapEvent {
+    /// A tile was placed in the cell, either in an empty cell or replacing another tile.
+    TileChanged(IntVector2),
+    TileRemoved(IntVector2),
+    VisibilityChanged(IntVector2, bool),
+    VisitedChanged(IntVector2, bool),
+    /// Gas or smoke started or stopped hiding the cell, see `FovOccluder::is_obscured`.
+    ObscuredChanged(IntVector2, bool),
+    ItemAdded(IntVector2, ItemKey),
+    ItemRemoved(IntVector2, ItemKey),
+}
+
+impl MapEvent {
+    pub fn position(&self) -> IntVector2 {
+        match self {
+            MapEvent::TileChanged(pos)
+            | MapEvent::TileRemoved(pos)
+            | MapEvent::VisibilityChanged(pos, _)
+            | MapEvent::VisitedChanged(pos, _)
+            | MapEvent::ObscuredChanged(pos, _)
+            | MapEvent::ItemAdded(pos, _)
+            | MapEvent::ItemRemoved(pos, _) => *pos,
+        }
+    }
+}
+
+/// Handle returned by `Map::subscribe`, used to poll the events queued for that subscriber.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriberId(usize);
+
+/// Per-subscriber event queues plus the bounding box of every cell changed since the last
+/// call to `take_dirty_region`.
+#[derive(Debug, Clone, Default)]
+pub(super) struct MapEvents {
+    queues: HashMap<SubscriberId, Vec<MapEvent>>,
+    next_id: usize,
+    dirty: Option<(IntVector2, IntVector2)>,
+}
+
+impl MapEvents {
+    pub fn subscribe(&mut self) -> SubscriberId {
+        let id = SubscriberId(self.next_id);
+        self.next_id += 1;
+        self.queues.insert(id, Vec::new());
+        id
+    }
+
+    pub fn unsubscribe(&mut self, id: SubscriberId) -> bool {
+        self.queues.remove(&id).is_some()
+    }
+
+    pub fn poll(&mut self, id: SubscriberId) -> Vec<MapEvent> {
+        self.queues
+            .get_mut(&id)
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    pub fn emit(&mut self, event: MapEvent) {
+        let pos = event.position();
+        self.dirty = Some(match self.dirty {
+            Some((min, max)) => (
+                IntVector2::new(min.x().min(pos.x()), min.y().min(pos.y())),
+                IntVector2::new(max.x().max(pos.x()), max.y().max(pos.y())),
+            ),
+            None => (pos, pos),
+        });
+        for queue in self.queues.values_mut() {
+            queue.push(event);
+        }
+    }
+
+    pub fn take_dirty_region(&mut self) -> Option<IntExtent2D> {
+        self.dirty.take().map(|(min, max)| {
+            IntExtent2D::new(
+                min.x(),
+                min.y(),
+                (max.x() - min.x() + 1) as usize,
+                (max.y() - min.y() + 1) as usize,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::VisibleTile, Dimension2D, Map, MapCommand};
+
+    #[test]
+    fn test_subscribers_receive_changes() {
+        let mut map = Map::<VisibleTile>::new(IntExtent2D::new(0, 0, 8, 8), Dimension2D::new(8, 8));
+        let early = map.subscribe();
+        map.set(1, 1, VisibleTile::default());
+        let late = map.subscribe();
+
+        map.set_visible(1, 1, true);
+        // unchanged visibility is not reported
+        map.set_visible(1, 1, true);
+        map.remove(2, 2);
+
+        assert_eq!(
+            map.poll_events(early),
+            vec![
+                MapEvent::TileChanged(IntVector2::new(1, 1)),
+                MapEvent::VisibilityChanged(IntVector2::new(1, 1), true),
+            ]
+        );
+        assert_eq!(
+            map.poll_events(late),
+            vec![MapEvent::VisibilityChanged(IntVector2::new(1, 1), true)]
+        );
+        assert!(map.poll_events(early).is_empty());
+
+        assert!(map.unsubscribe(late));
+        map.remove(1, 1);
+        assert!(map.poll_events(late).is_empty());
+        assert_eq!(
+            map.poll_events(early),
+            vec![MapEvent::TileRemoved(IntVector2::new(1, 1))]
+        );
+    }
+
+    #[test]
+    fn test_dirty_region() {
+        let mut map = Map::<VisibleTile>::new(IntExtent2D::new(0, 0, 8, 8), Dimension2D::new(8, 8));
+        assert!(map.take_dirty_region().is_none());
+
+        {
+            let mut batch = map.batch();
+            batch.set(2, 5, VisibleTile::default());
+            batch.set(4, 1, VisibleTile::default());
+            batch.add_command(MapCommand::SetVisible(IntVector2::new(2, 5), true));
+        }
+
+        let region = map.take_dirty_region().unwrap();
+        assert_eq!(
+            (region.left(), region.top(), region.width(), region.height()),
+            (2, 1, 3, 5)
+        );
+        assert!(map.take_dirty_region().is_none());
+    }
+}
@@ -2,6 +2,51 @@ use std::collections::HashSet;
 
 use crate::{IntVector2, Map, Tile, Vec2};
 
+/// Computes the cells visible from `center` within a square of the given `radius`.
+///
+/// Rays are cast from `center` to every cell on the border of the square; a ray stops
+/// at the first empty cell or at the first tile that blocks visibility. Blocking tiles
+/// themselves are visible, so walls show up at the edge of the field of view.
+pub fn compute_fov<T: Tile>(map: &Map<T>, center: IntVector2, radius: i32) -> HashSet<IntVector2> {
+    compute_fov_with(map, center, radius, |_| false)
+}
+
+/// Like `compute_fov`, with `blocks` marking extra cells that stop sight, such as thick gas.
+pub fn compute_fov_with<T: Tile, F: Fn(IntVector2) -> bool>(
+    map: &Map<T>,
+    center: IntVector2,
+    radius: i32,
+    blocks: F,
+) -> HashSet<IntVector2> {
+    let mut visible = HashSet::new();
+    if map.get(center.x(), center.y()).is_some() {
+        visible.insert(center);
+    }
+
+    for i in -radius..=radius {
+        for j in -radius..=radius {
+            if i.abs() != radius && j.abs() != radius {
+                continue;
+            }
+            let target = IntVector2::new(center.x() + i, center.y() + j);
+            for p in map.line(center, target) {
+                match map.get(p.x(), p.y()) {
+                    Some(tile) => {
+                        visible.insert(p);
+                        if tile.block_visibility() == T::BLOCKED
+                            || (p != center && (tile.is_obscured() || blocks(p)))
+                        {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+    visible
+}
+
 // pub struct MapFovIter<'a, T: Tile> {
 //     map: &'a Map<T>,
 //     center: IntVector2,
@@ -56,3 +101,45 @@ use crate::{IntVector2, Map, Tile, Vec2};
 //         next
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Dimension2D, FovOccluder, IntExtent2D, ItemContainer, VisibilityOcclusion, Visible,
+        Visited, Walkable,
+    };
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestTile {
+        wall: bool,
+    }
+    impl Tile for TestTile {}
+    impl Visible for TestTile {}
+    impl Visited for TestTile {}
+    impl FovOccluder for TestTile {
+        fn block_visibility(&self) -> VisibilityOcclusion {
+            if self.wall {
+                Self::BLOCKED
+            } else {
+                Self::VISIBLE
+            }
+        }
+    }
+    impl Walkable for TestTile {}
+    impl ItemContainer for TestTile {}
+
+    #[test]
+    fn test_compute_fov() {
+        // a corridor with a wall at x = 3
+        let mut map = Map::new(IntExtent2D::new(0, 0, 7, 1), Dimension2D::new(1, 1));
+        for x in 0..7 {
+            map.set(x, 0, TestTile { wall: x == 3 });
+        }
+
+        let fov = compute_fov(&map, IntVector2::new(1, 0), 5);
+        let mut cells = fov.iter().map(|p| p.x()).collect::<Vec<_>>();
+        cells.sort();
+        assert_eq!(cells, vec![0, 1, 2, 3]);
+    }
+}
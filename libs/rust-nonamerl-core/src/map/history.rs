@@ -0,0 +1,278 @@
+use crate::{Map, MapCommand, MapCommands, Tile};
+
+/// A position in a `MapHistory` that can be rolled back to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+/// Records the inverse of every executed command so map edits can be undone and redone.
+///
+/// Each call to `execute` or `execute_all` is one undo step. Checkpoints let a caller
+/// try out a sequence of edits (e.g. an AI simulating a move) and throw them away.
+#[derive(Debug, Clone)]
+pub struct MapHistory<T: Tile> {
+    undo: Vec<Vec<MapCommand<T>>>,
+    redo: Vec<Vec<MapCommand<T>>>,
+}
+
+impl<T: Tile> MapHistory<T> {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Applies `command` as a single undo step. Returns `false` if the command had no effect.
+    pub fn execute(&mut self, map: &mut Map<T>, command: MapCommand<T>) -> bool {
+        self.execute_all(map, vec![command])
+    }
+
+    /// Applies `commands` in order as a single undo step.
+    pub fn execute_all(&mut self, map: &mut Map<T>, commands: Vec<MapCommand<T>>) -> bool {
+        let mut commands = MapCommands { commands };
+        let inverses = commands.process_commands(map);
+        if inverses.is_empty() {
+            return false;
+        }
+        self.undo.push(inverses);
+        self.redo.clear();
+        true
+    }
+
+    pub fn undo(&mut self, map: &mut Map<T>) -> bool {
+        match self.undo.pop() {
+            Some(commands) => {
+                self.redo
+                    .push(MapCommands { commands }.process_commands(map));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self, map: &mut Map<T>) -> bool {
+        match self.redo.pop() {
+            Some(commands) => {
+                self.undo
+                    .push(MapCommands { commands }.process_commands(map));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.undo.len())
+    }
+
+    /// Undoes every step executed after `checkpoint`. The undone steps cannot be redone.
+    pub fn rollback(&mut self, map: &mut Map<T>, checkpoint: Checkpoint) {
+        while self.undo.len() > checkpoint.0 && self.undo(map) {}
+        self.redo.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+impl<T: Tile> Default for MapHistory<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        world::ItemKey, Dimension2D, FovOccluder, IntExtent2D, IntVector2, ItemContainer,
+        TileTransition, Visible, Visited, Walkable,
+    };
+    use slotmap::SlotMap;
+
+    #[derive(Debug, Clone, Default, PartialEq)]
+    struct TestTile {
+        kind: u8,
+        visited: bool,
+        items: Vec<ItemKey>,
+        durability: Option<u32>,
+    }
+    impl Tile for TestTile {
+        fn durability(&self) -> Option<u32> {
+            self.durability
+        }
+        fn set_durability(&mut self, durability: u32) {
+            self.durability = Some(durability);
+        }
+        fn transition(&self, transition: TileTransition) -> Option<Self> {
+            match transition {
+                TileTransition::Destroyed if self.kind == 3 => Some(tile(0)),
+                TileTransition::Opened => Some(tile(self.kind + 1)),
+                _ => None,
+            }
+        }
+    }
+    impl Visible for TestTile {}
+    impl Visited for TestTile {
+        fn is_visited(&self) -> bool {
+            self.visited
+        }
+        fn set_visited(&mut self, visited: bool) {
+            self.visited = visited;
+        }
+    }
+    impl FovOccluder for TestTile {}
+    impl Walkable for TestTile {}
+    impl ItemContainer for TestTile {
+        fn items(&self) -> &[ItemKey] {
+            &self.items
+        }
+        fn add_item(&mut self, item: ItemKey) {
+            self.items.push(item);
+        }
+        fn remove_item(&mut self, item: ItemKey) {
+            self.items.retain(|i| *i != item);
+        }
+    }
+
+    fn tile(kind: u8) -> TestTile {
+        TestTile {
+            kind,
+            ..Default::default()
+        }
+    }
+
+    fn map() -> Map<TestTile> {
+        let mut map = Map::new(IntExtent2D::new(0, 0, 4, 4), Dimension2D::new(8, 8));
+        for x in 0..4 {
+            map.set(x, 0, tile(0));
+        }
+        map
+    }
+
+    #[test]
+    fn test_inverse_commands() {
+        let mut map = map();
+        let pos = IntVector2::new(1, 0);
+        let item = SlotMap::<ItemKey, ()>::with_key().insert(());
+
+        let inverse = MapCommand::SetTile(pos, tile(1)).apply(&mut map);
+        assert_eq!(inverse, Some(MapCommand::SetTile(pos, tile(0))));
+        let inverse = MapCommand::SetTile(IntVector2::new(3, 3), tile(1)).apply(&mut map);
+        assert_eq!(inverse, Some(MapCommand::RemoveTile(IntVector2::new(3, 3))));
+
+        assert_eq!(MapCommand::RemoveItem(pos, item).apply(&mut map), None);
+        MapCommand::AddItem(pos, item).apply(&mut map);
+        let inverse = MapCommand::MoveItem {
+            from: pos,
+            to: IntVector2::new(2, 0),
+            item,
+        }
+        .apply(&mut map)
+        .unwrap();
+        assert_eq!(map.get(2, 0).unwrap().items(), &[item]);
+        inverse.apply(&mut map);
+        assert_eq!(map.get(1, 0).unwrap().items(), &[item]);
+        assert!(map.get(2, 0).unwrap().items().is_empty());
+    }
+
+    #[test]
+    fn test_damage_and_transform() {
+        let mut map = map();
+        let wall = |durability| TestTile {
+            kind: 3,
+            durability: Some(durability),
+            ..Default::default()
+        };
+        map.set(0, 0, wall(4));
+        map.set(1, 0, TestTile { kind: 4, ..wall(1) });
+
+        let pos = IntVector2::new(0, 0);
+        assert_eq!(
+            MapCommand::DamageTile(pos, 3).apply(&mut map),
+            Some(MapCommand::SetTile(pos, wall(4)))
+        );
+        assert_eq!(map.get(0, 0), Some(&wall(1)));
+        MapCommand::DamageTile(pos, 3).apply(&mut map);
+        assert_eq!(map.get(0, 0), Some(&tile(0)));
+        // tiles without durability cannot be damaged
+        assert_eq!(MapCommand::DamageTile(pos, 3).apply(&mut map), None);
+
+        // no destroyed transition, so the tile is removed
+        let inverse = MapCommand::DamageTile(IntVector2::new(1, 0), 1).apply(&mut map);
+        assert_eq!(map.get(1, 0), None);
+        inverse.unwrap().apply(&mut map);
+        assert_eq!(map.get(1, 0).unwrap().kind, 4);
+
+        let inverse = MapCommand::TransformTile(pos, TileTransition::Opened).apply(&mut map);
+        assert_eq!(map.get(0, 0), Some(&tile(1)));
+        assert_eq!(inverse, Some(MapCommand::SetTile(pos, tile(0))));
+        assert_eq!(
+            MapCommand::TransformTile(pos, TileTransition::Burnt).apply(&mut map),
+            None
+        );
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut map = map();
+        let mut history = MapHistory::new();
+
+        history.execute_all(
+            &mut map,
+            vec![
+                MapCommand::SetTile(IntVector2::new(0, 0), tile(2)),
+                MapCommand::SetVisited(IntVector2::new(0, 0), true),
+                MapCommand::RemoveTile(IntVector2::new(3, 0)),
+            ],
+        );
+        assert!(map.get(0, 0).unwrap().visited);
+        assert_eq!(map.get(3, 0), None);
+
+        assert!(history.undo(&mut map));
+        assert_eq!(map.get(0, 0), Some(&tile(0)));
+        assert_eq!(map.get(3, 0), Some(&tile(0)));
+        assert!(!history.undo(&mut map));
+
+        assert!(history.redo(&mut map));
+        assert_eq!(map.get(0, 0).unwrap().kind, 2);
+        assert!(map.get(0, 0).unwrap().visited);
+        assert_eq!(map.get(3, 0), None);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut map = map();
+        let mut history = MapHistory::new();
+        history.execute(
+            &mut map,
+            MapCommand::SetTile(IntVector2::new(0, 0), tile(1)),
+        );
+
+        let checkpoint = history.checkpoint();
+        for x in 0..4 {
+            history.execute(
+                &mut map,
+                MapCommand::SetTile(IntVector2::new(x, 1), tile(5)),
+            );
+        }
+        assert_eq!(map.len(), 8);
+
+        history.rollback(&mut map, checkpoint);
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get(0, 0), Some(&tile(1)));
+        assert!(history.can_undo());
+        assert!(!history.can_redo());
+    }
+}
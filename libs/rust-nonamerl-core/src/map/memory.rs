@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::{world::ItemKey, IntVector2, Map, Tile, Vec2};
+
+/// What a cell looked like the last time it was seen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RememberedTile<T: Tile> {
+    pub tile: T,
+    pub items: Vec<ItemKey>,
+}
+
+/// The cells an entity has seen, as they were when it last saw them.
+///
+/// The memory is only refreshed by `update`, so it can be stale: a door remembered
+/// open may have been closed since, an item remembered on the floor may be gone.
+#[derive(Debug, Clone)]
+pub struct MapMemory<T: Tile> {
+    tiles: HashMap<IntVector2, RememberedTile<T>>,
+}
+
+impl<T: Tile> MapMemory<T> {
+    pub fn new() -> Self {
+        Self {
+            tiles: HashMap::new(),
+        }
+    }
+
+    /// Records the current state of every cell in `visible`. Returns the number of cells
+    /// remembered for the first time.
+    ///
+    /// A visible cell that no longer holds a tile is forgotten.
+    pub fn update<I: IntoIterator<Item = IntVector2>>(
+        &mut self,
+        map: &Map<T>,
+        visible: I,
+    ) -> usize {
+        let mut discovered = 0;
+        for pos in visible {
+            match map.get(pos.x(), pos.y()) {
+                Some(tile) => {
+                    let remembered = RememberedTile {
+                        tile: tile.clone(),
+                        items: tile.items().to_vec(),
+                    };
+                    if self.tiles.insert(pos, remembered).is_none() {
+                        discovered += 1;
+                    }
+                }
+                None => {
+                    self.tiles.remove(&pos);
+                }
+            }
+        }
+        discovered
+    }
+
+    pub fn get(&self, pos: IntVector2) -> Option<&RememberedTile<T>> {
+        self.tiles.get(&pos)
+    }
+
+    pub fn is_remembered(&self, pos: IntVector2) -> bool {
+        self.tiles.contains_key(&pos)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (IntVector2, &RememberedTile<T>)> {
+        self.tiles
+            .iter()
+            .map(|(pos, remembered)| (*pos, remembered))
+    }
+
+    pub fn forget(&mut self, pos: IntVector2) -> Option<RememberedTile<T>> {
+        self.tiles.remove(&pos)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+}
+
+impl<T: Tile> Default for MapMemory<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::WalkableTile, Dimension2D, IntExtent2D};
+
+    #[test]
+    fn test_memory_keeps_last_seen_state() {
+        let mut map = Map::new(IntExtent2D::new(0, 0, 3, 1), Dimension2D::new(1, 1));
+        map.set(0, 0, WalkableTile { walkable: true });
+        map.set(1, 0, WalkableTile { walkable: true });
+
+        let mut memory = MapMemory::new();
+        memory.update(&map, [IntVector2::new(0, 0), IntVector2::new(1, 0)]);
+        map.set(0, 0, WalkableTile { walkable: false });
+
+        // not seen again, so the old state is still remembered
+        assert_eq!(
+            memory.get(IntVector2::new(0, 0)).unwrap().tile,
+            WalkableTile { walkable: true }
+        );
+
+        map.remove(1, 0);
+        memory.update(&map, [IntVector2::new(0, 0), IntVector2::new(1, 0)]);
+        assert_eq!(
+            memory.get(IntVector2::new(0, 0)).unwrap().tile,
+            WalkableTile { walkable: false }
+        );
+        assert!(!memory.is_remembered(IntVector2::new(1, 0)));
+        assert_eq!(memory.len(), 1);
+    }
+}
@@ -1,104 +1,201 @@
 #![allow(dead_code)]
-use std::{cell::RefCell, fmt::Debug};
+use std::fmt::Debug;
 
 use crate::{
     dimension::{Dimension2, Dimension2D, IntExtent2D},
     grid::LatticeGrid2D,
     vector::{IntVector2, Vec2},
+    world::ItemKey,
     SpriteSheet,
 };
 
+mod autotile;
+mod batch;
 mod builder;
 mod commands;
+mod environment;
+mod events;
 mod fov;
+mod history;
+mod memory;
 mod noise_builder;
 mod random_walk_builder;
+mod report;
 mod room;
 mod room_builder;
+mod smoothing;
 mod tile;
+mod tile_registry;
+mod trigger;
 
-pub use builder::{MapBuilder, MapBuilderAlgorithm};
+pub use autotile::*;
+pub use batch::MapBatch;
+pub use builder::{MapBuilder, MapBuilderAlgorithm, MapBuilderError};
 pub use commands::*;
+pub use environment::*;
+use events::MapEvents;
+pub use events::{MapEvent, SubscriberId};
 pub use fov::*;
+pub use history::*;
 use macroquad::{
     prelude::{Color, Rect},
     texture::Texture2D,
 };
+pub use memory::*;
 pub use noise_builder::BuilderAlgoWithNoise;
 pub use random_walk_builder::RandomWalkBuilder;
+pub use report::*;
 pub use room::*;
 pub use room_builder::*;
+pub use smoothing::*;
 pub use tile::*;
+pub use tile_registry::*;
+pub use trigger::*;
 
 #[derive(Clone, Debug)]
 pub struct Map<T: Tile> {
-    grid: RefCell<LatticeGrid2D<T>>,
+    grid: LatticeGrid2D<T>,
     extent: IntExtent2D,
     cell_size: Dimension2D<usize>,
-    commands: RefCell<MapCommands>,
+    commands: MapCommands<T>,
+    events: MapEvents,
 }
 
 impl<T: Tile> Map<T> {
     pub fn new(extent: IntExtent2D, cell_size: Dimension2D<usize>) -> Self {
         Self {
-            grid: RefCell::new(LatticeGrid2D::<T>::new()),
+            grid: LatticeGrid2D::<T>::new(),
             extent,
             cell_size,
-            commands: RefCell::new(MapCommands::default()),
+            commands: MapCommands::default(),
+            events: MapEvents::default(),
         }
     }
 
-    pub fn add_command(&self, command: MapCommand) {
-        self.commands.borrow_mut().add(command);
+    /// Queues a command, applied on the next call to `process_commands`.
+    pub fn add_command(&mut self, command: MapCommand<T>) {
+        self.commands.add(command);
     }
 
-    pub fn add_commands(&self, commands: Vec<MapCommand>) {
-        self.commands.borrow_mut().add_all(commands);
+    pub fn add_commands(&mut self, commands: Vec<MapCommand<T>>) {
+        self.commands.add_all(commands);
     }
 
     pub fn process_commands(&mut self) {
-        for command in self.commands.borrow_mut().commands.iter() {
-            match command {
-                MapCommand::SetVisited(pos, visited) => {
-                    self.set_visited(pos.x(), pos.y(), *visited);
-                }
-                MapCommand::SetVisible(pos, visible) => {
-                    self.set_visible(pos.x(), pos.y(), *visible);
-                }
-                MapCommand::AddItem(pos, item) => {
-                    self.add_item(pos.x(), pos.y(), *item);
-                }
+        let mut commands = std::mem::take(&mut self.commands);
+        commands.process_commands(self);
+    }
+
+    pub fn commands_available(&self) -> bool {
+        !self.commands.is_empty()
+    }
+
+    pub fn set_visited(&mut self, x: i32, y: i32, visited: bool) {
+        if let Some(tile) = self.grid.at_mut(IntVector2::new(x, y)) {
+            if tile.is_visited() != visited {
+                tile.set_visited(visited);
+                self.events
+                    .emit(MapEvent::VisitedChanged(IntVector2::new(x, y), visited));
             }
         }
-        self.commands.borrow_mut().clear();
     }
 
-    pub fn commands_available(&self) -> bool {
-        !self.commands.borrow().is_empty()
+    pub fn set_visible(&mut self, x: i32, y: i32, visible: bool) {
+        if let Some(tile) = self.grid.at_mut(IntVector2::new(x, y)) {
+            if tile.is_visible() != visible {
+                tile.set_visible(visible);
+                self.events
+                    .emit(MapEvent::VisibilityChanged(IntVector2::new(x, y), visible));
+            }
+        }
     }
 
-    pub fn set_visited(&self, x: i32, y: i32, visited: bool) {
-        if let Some(tile) = self.grid.borrow_mut().at_mut(IntVector2::new(x, y)) {
-            tile.set_visited(visited);
+    pub fn set_obscured(&mut self, x: i32, y: i32, obscured: bool) {
+        if let Some(tile) = self.grid.at_mut(IntVector2::new(x, y)) {
+            if tile.is_obscured() != obscured {
+                tile.set_obscured(obscured);
+                self.events
+                    .emit(MapEvent::ObscuredChanged(IntVector2::new(x, y), obscured));
+            }
         }
     }
 
-    pub fn set_visible(&self, x: i32, y: i32, visible: bool) {
-        if let Some(tile) = self.grid.borrow_mut().at_mut(IntVector2::new(x, y)) {
-            tile.set_visible(visible);
+    /// Puts `item` in the tile at `(x, y)`. Returns `false` if the cell is empty.
+    pub fn add_item(&mut self, x: i32, y: i32, item: ItemKey) -> bool {
+        match self.grid.at_mut(IntVector2::new(x, y)) {
+            Some(tile) => {
+                tile.add_item(item);
+                self.events
+                    .emit(MapEvent::ItemAdded(IntVector2::new(x, y), item));
+                true
+            }
+            None => false,
         }
     }
 
-    pub fn get(&self, x: i32, y: i32) -> Option<T> {
-        let binding = self.grid.borrow();
-        match self.grid.borrow().at(IntVector2::new(x, y)) {
-            Some(tile) => Some((*tile).clone()),
-            None => None,
+    /// Takes `item` out of the tile at `(x, y)`. Returns `false` if the item was not there.
+    pub fn remove_item(&mut self, x: i32, y: i32, item: ItemKey) -> bool {
+        match self.grid.at_mut(IntVector2::new(x, y)) {
+            Some(tile) if tile.items().contains(&item) => {
+                tile.remove_item(item);
+                self.events
+                    .emit(MapEvent::ItemRemoved(IntVector2::new(x, y), item));
+                true
+            }
+            _ => false,
         }
     }
 
-    pub fn set(&self, x: i32, y: i32, tile: T) {
-        self.grid.borrow_mut().put(IntVector2::new(x, y), tile);
+    pub fn get(&self, x: i32, y: i32) -> Option<&T> {
+        self.grid.at(IntVector2::new(x, y))
+    }
+
+    /// Mutable access to a tile. Changes made through it are not reported to subscribers.
+    pub fn get_mut(&mut self, x: i32, y: i32) -> Option<&mut T> {
+        self.grid.at_mut(IntVector2::new(x, y))
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, tile: T) {
+        self.replace(x, y, tile);
+    }
+
+    /// Places `tile` at `(x, y)`, returning the tile it replaced.
+    pub fn replace(&mut self, x: i32, y: i32, tile: T) -> Option<T> {
+        let pos = IntVector2::new(x, y);
+        self.events.emit(MapEvent::TileChanged(pos));
+        self.grid.replace(pos, tile)
+    }
+
+    pub fn remove(&mut self, x: i32, y: i32) -> Option<T> {
+        let pos = IntVector2::new(x, y);
+        let tile = self.grid.remove(pos)?;
+        self.events.emit(MapEvent::TileRemoved(pos));
+        Some(tile)
+    }
+
+    /// Registers a new subscriber. Every change made from now on is queued for it
+    /// until it is collected with `poll_events`.
+    pub fn subscribe(&mut self) -> SubscriberId {
+        self.events.subscribe()
+    }
+
+    pub fn unsubscribe(&mut self, id: SubscriberId) -> bool {
+        self.events.unsubscribe(id)
+    }
+
+    /// Drains the events queued for `id`, oldest first.
+    pub fn poll_events(&mut self, id: SubscriberId) -> Vec<MapEvent> {
+        self.events.poll(id)
+    }
+
+    /// Returns the smallest extent covering every cell changed since the previous call.
+    pub fn take_dirty_region(&mut self) -> Option<IntExtent2D> {
+        self.events.take_dirty_region()
+    }
+
+    /// Starts a batch of edits that are applied together when the returned guard is dropped.
+    pub fn batch(&mut self) -> MapBatch<'_, T> {
+        MapBatch::new(self)
     }
 
     pub fn size(&self) -> IntExtent2D {
@@ -106,11 +203,11 @@ impl<T: Tile> Map<T> {
     }
 
     pub fn len(&self) -> usize {
-        self.grid.borrow().len()
+        self.grid.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.grid.borrow().is_empty()
+        self.grid.is_empty()
     }
 
     pub fn cell_size(&self) -> Dimension2D<usize> {
@@ -118,7 +215,19 @@ impl<T: Tile> Map<T> {
     }
 
     pub fn line(&self, start: IntVector2, end: IntVector2) -> Vec<IntVector2> {
-        self.grid.borrow().line(start, end)
+        self.grid.line(start, end)
+    }
+
+    /// Returns the 4-connected neighbours of `pos`, whether or not they hold a tile.
+    pub fn neighbors(&self, pos: IntVector2) -> Vec<IntVector2> {
+        self.grid.neighbors(pos)
+    }
+
+    /// Returns `true` if the cell holds a walkable tile.
+    pub fn is_walkable(&self, x: i32, y: i32) -> bool {
+        self.get(x, y)
+            .map(|tile| tile.is_walkable())
+            .unwrap_or(false)
     }
 
     pub fn coords_of_cell(&self, x: i32, y: i32) -> Option<IntVector2> {
@@ -132,16 +241,10 @@ impl<T: Tile> Map<T> {
         }
     }
 
-    fn add_item(&self, x: i32, y: i32, item: crate::world::ItemKey) {
-        if let Some(tile) = self.grid.borrow_mut().at_mut(IntVector2::new(x, y)) {
-            tile.add_item(item);
-        }
-    }
-
     pub fn iter_over_visible_tiles<'a>(
         &'a self,
         extent: &'a IntExtent2D,
-    ) -> MapVisibleTilesIter<T> {
+    ) -> MapVisibleTilesIter<'a, T> {
         MapVisibleTilesIter::new(self, extent)
     }
 
@@ -167,7 +270,7 @@ impl<'a, T: Tile> MapVisibleTilesIter<'a, T> {
 }
 
 impl<'a, T: Tile> Iterator for MapVisibleTilesIter<'a, T> {
-    type Item = (IntVector2, T);
+    type Item = (IntVector2, &'a T);
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.current.y() >= self.extent.bottom() {
@@ -212,14 +315,10 @@ mod tests {
     fn test_map() {
         let mut map =
             Map::<TestTile>::new(IntExtent2D::new(0, 0, 10, 10), Dimension2D::new(24, 24));
-        map.grid
-            .borrow_mut()
-            .put(IntVector2::new(1, 1), TestTile::default());
-        map.grid
-            .borrow_mut()
-            .put(IntVector2::new(0, 1), TestTile::default());
-        assert_eq!(map.get(1, 1), Some(TestTile::default()));
-        assert_eq!(map.get(0, 1), Some(TestTile::default()));
+        map.grid.put(IntVector2::new(1, 1), TestTile::default());
+        map.grid.put(IntVector2::new(0, 1), TestTile::default());
+        assert_eq!(map.get(1, 1), Some(&TestTile::default()));
+        assert_eq!(map.get(0, 1), Some(&TestTile::default()));
         assert_eq!(map.get(0, 0), None);
         assert_eq!(map.len(), 2);
     }
@@ -1,4 +1,4 @@
-use crate::{MapBuilder, MapBuilderAlgorithm, Tile};
+use crate::{MapBuilder, MapBuilderAlgorithm, MapBuilderError, Tile};
 
 use noise::NoiseFn;
 
@@ -27,7 +27,10 @@ impl<T: Tile, N: NoiseFn<f64, 2>, F: Fn(i32, i32, f64) -> Option<T>> BuilderAlgo
 impl<T: Tile, N: NoiseFn<f64, 2>, F: Fn(i32, i32, f64) -> Option<T>> MapBuilderAlgorithm<T>
     for BuilderAlgoWithNoise<T, N, F>
 {
-    fn build<'a>(&self, map_builder: &'a mut MapBuilder<T>) -> &'a mut MapBuilder<T> {
+    fn build<'a>(
+        &self,
+        map_builder: &'a mut MapBuilder<T>,
+    ) -> Result<&'a mut MapBuilder<T>, MapBuilderError> {
         for x in map_builder.map.extent.left()..map_builder.map.extent.right() {
             for y in map_builder.map.extent.top()..map_builder.map.extent.bottom() {
                 let value = self.noise.get([x as f64 * 5., y as f64 * 5.]);
@@ -39,7 +42,7 @@ impl<T: Tile, N: NoiseFn<f64, 2>, F: Fn(i32, i32, f64) -> Option<T>> MapBuilderA
             }
         }
 
-        map_builder
+        Ok(map_builder)
     }
 }
 
@@ -81,7 +84,9 @@ mod tests {
             }
         };
 
-        map_builder.add_step(&BuilderAlgoWithNoise::new(noise, f));
+        map_builder
+            .add_step(&BuilderAlgoWithNoise::new(noise, f))
+            .unwrap();
         let map = map_builder.map;
 
         assert_eq!(map.len(), 53);
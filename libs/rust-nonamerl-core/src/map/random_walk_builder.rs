@@ -1,8 +1,6 @@
-use std::collections::{HashMap, HashSet};
+use rand::seq::SliceRandom;
 
-use rand::seq::{IteratorRandom, SliceRandom};
-
-use crate::{IntVector2, MapBuilder, MapBuilderAlgorithm, Tile, Vec2};
+use crate::{IntVector2, MapBuilder, MapBuilderAlgorithm, MapBuilderError, Tile, Vec2};
 
 #[derive(Debug, Clone)]
 pub struct RandomWalkBuilder<T>
@@ -23,20 +21,23 @@ impl<T: Tile> RandomWalkBuilder<T> {
 }
 
 impl<T: Tile> MapBuilderAlgorithm<T> for RandomWalkBuilder<T> {
-    fn build<'a>(&self, map_builder: &'a mut MapBuilder<T>) -> &'a mut MapBuilder<T> {
-        let mut rng = rand::thread_rng();
+    fn build<'a>(
+        &self,
+        map_builder: &'a mut MapBuilder<T>,
+    ) -> Result<&'a mut MapBuilder<T>, MapBuilderError> {
         let pos = self.start_pos;
 
         let mut current_pos = self.start_pos;
 
-        let mut visited = HashSet::<IntVector2>::new();
+        // a Vec, not a set: picking from it must not depend on hash order for seeded builds
+        let mut visited = Vec::<IntVector2>::new();
         let directions = ["up", "down", "left", "right"];
         // generate a random walk
         while visited.len() < 100 {
             let mut next_pos = current_pos;
 
             // randomly choose a direction
-            let direction = directions.choose(&mut rng).unwrap();
+            let direction = directions.choose(&mut map_builder.rng).unwrap();
             //let direction = directions[dir];
 
             match *direction {
@@ -47,22 +48,54 @@ impl<T: Tile> MapBuilderAlgorithm<T> for RandomWalkBuilder<T> {
                 _ => {}
             }
 
-            if !visited.insert(next_pos) {
+            if visited.contains(&next_pos) {
                 // select random element from visited
-                current_pos = *visited.iter().choose(&mut rng).unwrap();
+                current_pos = *visited.choose(&mut map_builder.rng).unwrap();
             } else {
+                visited.push(next_pos);
                 current_pos = next_pos;
             }
         }
-        println!("visited: {:?}", visited);
         //map_builder.map_tiles.tiles = visited.clone();
+        let tile = map_builder.tile("floor")?;
         visited.iter().for_each(|pos| {
-            let tile = map_builder.tiles.get("floor").unwrap().clone();
-
-            map_builder.map.set(pos.x(), pos.y(), tile);
+            map_builder.map.set(pos.x(), pos.y(), tile.clone());
         });
         // println!("map_tiles: {:?}", map_builder.map_tiles.tiles);
 
+        Ok(map_builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::WalkableTile, Dimension2D, IntExtent2D, Map};
+
+    fn build(seed: u64) -> Map<WalkableTile> {
+        let mut map_builder = MapBuilder::with_seed(
+            IntExtent2D::new(0, 0, 100, 100),
+            Dimension2D::new(24, 24),
+            seed,
+        );
+        map_builder.add_tile("floor", WalkableTile { walkable: true });
         map_builder
+            .add_step(&RandomWalkBuilder::new(IntVector2::new(50, 50)))
+            .unwrap();
+        map_builder.map
+    }
+
+    #[test]
+    fn test_same_seed_same_walk() {
+        let floors = |map: &Map<WalkableTile>| {
+            map.size()
+                .iter()
+                .filter(|pos| map.get(pos.x(), pos.y()).is_some())
+                .collect::<Vec<_>>()
+        };
+        let walk = floors(&build(3));
+        assert_eq!(walk.len(), 100);
+        assert_eq!(walk, floors(&build(3)));
+        assert_ne!(walk, floors(&build(4)));
     }
 }
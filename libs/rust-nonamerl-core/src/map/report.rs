@@ -0,0 +1,417 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{self, Display},
+};
+
+use crate::{IntVector2, Map, MapBuilder, Room, Tile, Vec2};
+
+/// Generation metrics computed from a built map.
+///
+/// Only cells inside the map extent are taken into account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapReport {
+    pub total_cells: usize,
+    pub walkable_cells: usize,
+    /// Walkable cells divided by the number of cells in the extent.
+    pub walkable_ratio: f32,
+    /// Number of 4-connected walkable regions.
+    pub regions: usize,
+    /// Size of the biggest walkable region.
+    pub largest_region: usize,
+    pub rooms: usize,
+    /// Walkable cells with a single walkable neighbour.
+    pub dead_ends: usize,
+    /// Walkable cells that do not belong to any room.
+    pub corridor_length: usize,
+    /// Longest shortest-path (in steps) found inside a single region.
+    pub longest_path: usize,
+    /// Steps from start to exit, `None` if either is missing or unreachable.
+    pub start_to_exit: Option<usize>,
+}
+
+impl MapReport {
+    /// Computes the report for `map`.
+    ///
+    /// # Arguments
+    ///
+    /// * `rooms` - The rooms placed on the map, used for room and corridor metrics.
+    /// * `start` - The cell where the player starts, if any.
+    /// * `exit` - The cell of the level exit, if any.
+    pub fn from_map<T: Tile>(
+        map: &Map<T>,
+        rooms: &[Room],
+        start: Option<IntVector2>,
+        exit: Option<IntVector2>,
+    ) -> Self {
+        let extent = map.size();
+        let walkable: HashSet<IntVector2> = extent
+            .iter()
+            .filter(|pos| map.is_walkable(pos.x(), pos.y()))
+            .collect();
+
+        let total_cells = extent.width() * extent.height();
+        let walkable_ratio = if total_cells == 0 {
+            0.
+        } else {
+            walkable.len() as f32 / total_cells as f32
+        };
+
+        let dead_ends = walkable
+            .iter()
+            .filter(|pos| {
+                map.neighbors(**pos)
+                    .iter()
+                    .filter(|n| walkable.contains(n))
+                    .count()
+                    == 1
+            })
+            .count();
+
+        let room_cells: HashSet<IntVector2> = rooms.iter().flat_map(|room| room.cells()).collect();
+        let corridor_length = walkable
+            .iter()
+            .filter(|pos| !room_cells.contains(pos))
+            .count();
+
+        let mut seen = HashSet::<IntVector2>::new();
+        let mut regions = 0;
+        let mut largest_region = 0;
+        let mut longest_path = 0;
+        // sweep in extent order so the report does not depend on hash order
+        for pos in extent.iter().filter(|pos| walkable.contains(pos)) {
+            if seen.contains(&pos) {
+                continue;
+            }
+            let distances = distances_from(map, &walkable, pos);
+            regions += 1;
+            largest_region = largest_region.max(distances.len());
+            seen.extend(distances.keys());
+
+            // double sweep: the farthest cell from any cell is an end of a long path
+            let (far, _) = farthest(&distances);
+            let (_, length) = farthest(&distances_from(map, &walkable, far));
+            longest_path = longest_path.max(length);
+        }
+
+        let start_to_exit = match (start, exit) {
+            (Some(start), Some(exit)) if walkable.contains(&start) => {
+                distances_from(map, &walkable, start).get(&exit).copied()
+            }
+            _ => None,
+        };
+
+        Self {
+            total_cells,
+            walkable_cells: walkable.len(),
+            walkable_ratio,
+            regions,
+            largest_region,
+            rooms: rooms.len(),
+            dead_ends,
+            corridor_length,
+            longest_path,
+            start_to_exit,
+        }
+    }
+
+    /// Computes the report for the map held by `map_builder`, using the center
+    /// of the first room as start and the center of the last room as exit.
+    pub fn from_builder<T: Tile>(map_builder: &MapBuilder<T>) -> Self {
+        let rooms = &map_builder.rooms;
+        Self::from_map(
+            &map_builder.map,
+            rooms,
+            rooms.first().map(|room| room.center()),
+            rooms.last().map(|room| room.center()),
+        )
+    }
+
+    /// Checks the report against `constraints`, returning every violation found.
+    pub fn check(&self, constraints: &MapConstraints) -> Result<(), Vec<MapViolation>> {
+        let mut violations = Vec::new();
+
+        if let Some(min) = constraints.min_walkable_ratio {
+            if self.walkable_ratio < min {
+                violations.push(MapViolation::WalkableRatioTooLow(self.walkable_ratio, min));
+            }
+        }
+        if let Some(max) = constraints.max_walkable_ratio {
+            if self.walkable_ratio > max {
+                violations.push(MapViolation::WalkableRatioTooHigh(self.walkable_ratio, max));
+            }
+        }
+        if let Some(max) = constraints.max_regions {
+            if self.regions > max {
+                violations.push(MapViolation::TooManyRegions(self.regions, max));
+            }
+        }
+        if let Some(min) = constraints.min_rooms {
+            if self.rooms < min {
+                violations.push(MapViolation::TooFewRooms(self.rooms, min));
+            }
+        }
+        if let Some(max) = constraints.max_dead_ends {
+            if self.dead_ends > max {
+                violations.push(MapViolation::TooManyDeadEnds(self.dead_ends, max));
+            }
+        }
+        if let Some(min) = constraints.min_longest_path {
+            if self.longest_path < min {
+                violations.push(MapViolation::LongestPathTooShort(self.longest_path, min));
+            }
+        }
+        if let Some(min) = constraints.min_start_to_exit {
+            match self.start_to_exit {
+                Some(distance) if distance < min => {
+                    violations.push(MapViolation::ExitTooClose(distance, min));
+                }
+                None => violations.push(MapViolation::ExitUnreachable),
+                _ => {}
+            }
+        } else if constraints.exit_reachable && self.start_to_exit.is_none() {
+            violations.push(MapViolation::ExitUnreachable);
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Panics listing every violated constraint. Meant for tests running many seeds.
+    pub fn assert(&self, constraints: &MapConstraints) {
+        if let Err(violations) = self.check(constraints) {
+            let messages: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+            panic!(
+                "map violates constraints: {}\n{:?}",
+                messages.join(", "),
+                self
+            );
+        }
+    }
+}
+
+/// Breadth-first distances from `start` to every walkable cell of its region.
+fn distances_from<T: Tile>(
+    map: &Map<T>,
+    walkable: &HashSet<IntVector2>,
+    start: IntVector2,
+) -> HashMap<IntVector2, usize> {
+    let mut distances = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(pos) = queue.pop_front() {
+        let distance = distances[&pos];
+        for n in map.neighbors(pos) {
+            if walkable.contains(&n) && !distances.contains_key(&n) {
+                distances.insert(n, distance + 1);
+                queue.push_back(n);
+            }
+        }
+    }
+
+    distances
+}
+
+/// The cell farthest from the sweep start; ties go to the first cell in row order.
+fn farthest(distances: &HashMap<IntVector2, usize>) -> (IntVector2, usize) {
+    distances
+        .iter()
+        .max_by_key(|(pos, distance)| (**distance, Reverse((pos.y(), pos.x()))))
+        .map(|(pos, distance)| (*pos, *distance))
+        .unwrap()
+}
+
+/// Constraints a generated map must satisfy. Unset limits are not checked.
+#[derive(Debug, Clone, Default)]
+pub struct MapConstraints {
+    pub min_walkable_ratio: Option<f32>,
+    pub max_walkable_ratio: Option<f32>,
+    pub max_regions: Option<usize>,
+    pub min_rooms: Option<usize>,
+    pub max_dead_ends: Option<usize>,
+    pub min_longest_path: Option<usize>,
+    pub min_start_to_exit: Option<usize>,
+    pub exit_reachable: bool,
+}
+
+impl MapConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn walkable_ratio(mut self, min: f32, max: f32) -> Self {
+        self.min_walkable_ratio = Some(min);
+        self.max_walkable_ratio = Some(max);
+        self
+    }
+
+    pub fn max_regions(mut self, max: usize) -> Self {
+        self.max_regions = Some(max);
+        self
+    }
+
+    pub fn min_rooms(mut self, min: usize) -> Self {
+        self.min_rooms = Some(min);
+        self
+    }
+
+    pub fn max_dead_ends(mut self, max: usize) -> Self {
+        self.max_dead_ends = Some(max);
+        self
+    }
+
+    pub fn min_longest_path(mut self, min: usize) -> Self {
+        self.min_longest_path = Some(min);
+        self
+    }
+
+    /// Requires the exit to be reachable and at least `min` steps away from the start.
+    pub fn min_start_to_exit(mut self, min: usize) -> Self {
+        self.min_start_to_exit = Some(min);
+        self
+    }
+
+    pub fn exit_reachable(mut self) -> Self {
+        self.exit_reachable = true;
+        self
+    }
+}
+
+/// A constraint violated by a map. Values are `(actual, limit)`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapViolation {
+    WalkableRatioTooLow(f32, f32),
+    WalkableRatioTooHigh(f32, f32),
+    TooManyRegions(usize, usize),
+    TooFewRooms(usize, usize),
+    TooManyDeadEnds(usize, usize),
+    LongestPathTooShort(usize, usize),
+    ExitTooClose(usize, usize),
+    ExitUnreachable,
+}
+
+impl Display for MapViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapViolation::WalkableRatioTooLow(v, min) => {
+                write!(f, "walkable ratio {:.2} below {:.2}", v, min)
+            }
+            MapViolation::WalkableRatioTooHigh(v, max) => {
+                write!(f, "walkable ratio {:.2} above {:.2}", v, max)
+            }
+            MapViolation::TooManyRegions(v, max) => write!(f, "{} regions, max {}", v, max),
+            MapViolation::TooFewRooms(v, min) => write!(f, "{} rooms, min {}", v, min),
+            MapViolation::TooManyDeadEnds(v, max) => write!(f, "{} dead ends, max {}", v, max),
+            MapViolation::LongestPathTooShort(v, min) => {
+                write!(f, "longest path {} shorter than {}", v, min)
+            }
+            MapViolation::ExitTooClose(v, min) => {
+                write!(f, "exit {} steps from start, min {}", v, min)
+            }
+            MapViolation::ExitUnreachable => write!(f, "exit unreachable from start"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_support::WalkableTile, Dimension2D, IntExtent2D, RoomBuilder};
+
+    use super::*;
+
+    fn map_from(rows: &[&str]) -> Map<WalkableTile> {
+        let mut map = Map::new(
+            IntExtent2D::new(0, 0, rows[0].len(), rows.len()),
+            Dimension2D::new(24, 24),
+        );
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                map.set(x as i32, y as i32, WalkableTile { walkable: c == '.' });
+            }
+        }
+        map
+    }
+
+    #[test]
+    fn test_report_metrics() {
+        let map = map_from(&[
+            "#####", //
+            "#...#", //
+            "#.###", //
+            "#####", //
+            "#.#.#", //
+        ]);
+        let report = MapReport::from_map(
+            &map,
+            &[],
+            Some(IntVector2::new(1, 2)),
+            Some(IntVector2::new(3, 1)),
+        );
+
+        assert_eq!(report.total_cells, 25);
+        assert_eq!(report.walkable_cells, 6);
+        assert_eq!(report.regions, 3);
+        assert_eq!(report.largest_region, 4);
+        assert_eq!(report.dead_ends, 2);
+        assert_eq!(report.corridor_length, 6);
+        assert_eq!(report.longest_path, 3);
+        assert_eq!(report.start_to_exit, Some(3));
+    }
+
+    #[test]
+    fn test_report_constraints() {
+        let map = map_from(&[
+            "###", //
+            "#.#", //
+            "###", //
+        ]);
+        let report = MapReport::from_map(&map, &[], Some(IntVector2::new(1, 1)), None);
+
+        assert!(report.check(&MapConstraints::new().max_regions(1)).is_ok());
+        assert_eq!(
+            report.check(&MapConstraints::new().min_rooms(1).exit_reachable()),
+            Err(vec![
+                MapViolation::TooFewRooms(0, 1),
+                MapViolation::ExitUnreachable
+            ])
+        );
+    }
+
+    fn room_report(seed: u64) -> MapReport {
+        let mut map_builder = MapBuilder::<WalkableTile>::with_seed(
+            IntExtent2D::new(0, 0, 100, 100),
+            Dimension2D::new(24, 24),
+            seed,
+        );
+        map_builder.add_tile("floor", WalkableTile { walkable: true });
+        map_builder.add_tile("wall", WalkableTile { walkable: false });
+        map_builder.add_step(&RoomBuilder::new()).unwrap();
+        MapReport::from_builder(&map_builder)
+    }
+
+    fn room_constraints() -> MapConstraints {
+        MapConstraints::new()
+            .walkable_ratio(0.05, 0.95)
+            .min_rooms(1)
+            .min_longest_path(10)
+    }
+
+    #[test]
+    fn test_room_builder_constraints_hold_across_runs() {
+        for seed in 0..50 {
+            room_report(seed).assert(&room_constraints());
+        }
+        assert_eq!(room_report(7), room_report(7));
+    }
+
+    #[test]
+    #[ignore = "slow, run with --ignored before changing the room builder"]
+    fn test_room_builder_constraints_hold_across_thousands_of_runs() {
+        for seed in 0..5000 {
+            room_report(seed).assert(&room_constraints());
+        }
+    }
+}
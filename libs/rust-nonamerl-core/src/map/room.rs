@@ -73,13 +73,12 @@ impl Room {
         Self::new(IntVector2::new(x, y), Dimension2D::new(w, h))
     }
 
-    pub fn create_random_in_rect(
+    pub fn create_random_in_rect<R: Rng>(
+        rng: &mut R,
         top_left: IntVector2,
         size: Dimension2D<usize>,
         room_size_range: (Range<u16>, Range<u16>),
     ) -> Self {
-        let mut rng = rand::thread_rng();
-
         let x = rng.gen_range(top_left.x()..top_left.x() + size.width() as i32);
         let y = rng.gen_range(top_left.y()..top_left.y() + size.height() as i32);
 
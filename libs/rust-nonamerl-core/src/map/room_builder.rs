@@ -1,4 +1,6 @@
-use crate::{Dimension2D, IntVector2, MapBuilder, MapBuilderAlgorithm, Room, Tile, Vec2};
+use crate::{
+    Dimension2D, IntVector2, MapBuilder, MapBuilderAlgorithm, MapBuilderError, Room, Tile, Vec2,
+};
 
 use rand::Rng;
 
@@ -89,7 +91,12 @@ where
 }
 
 impl<T: Tile> MapBuilderAlgorithm<T> for RoomBuilder<T> {
-    fn build<'a>(&self, map_builder: &'a mut MapBuilder<T>) -> &'a mut MapBuilder<T> {
+    fn build<'a>(
+        &self,
+        map_builder: &'a mut MapBuilder<T>,
+    ) -> Result<&'a mut MapBuilder<T>, MapBuilderError> {
+        let floor = map_builder.tile("floor")?;
+        let wall = map_builder.tile("wall")?;
         let mut rooms = Vec::<Room>::new();
 
         // let mut room = Room::create_random(20, 20);
@@ -106,6 +113,7 @@ impl<T: Tile> MapBuilderAlgorithm<T> for RoomBuilder<T> {
         let map_extent = map_builder.map.size();
         while rooms.len() < 10 && attempts < 1000 {
             let candidate = Room::create_random_in_rect(
+                &mut map_builder.rng,
                 IntVector2::new(map_extent.left(), map_extent.top()),
                 Dimension2D::<usize>::new(map_extent.width(), map_extent.height()),
                 (10..25, 10..25),
@@ -126,17 +134,13 @@ impl<T: Tile> MapBuilderAlgorithm<T> for RoomBuilder<T> {
 
         rooms.iter().for_each(|room| {
             room.cells().iter().for_each(|pos| {
-                let tile = map_builder.tiles.get("floor").unwrap().clone();
-
-                map_builder.map.set(pos.x(), pos.y(), tile);
+                map_builder.map.set(pos.x(), pos.y(), floor.clone());
             });
         });
 
         rooms.iter().for_each(|room| {
             room.border_cells().iter().for_each(|pos| {
-                let tile = map_builder.tiles.get("wall").unwrap().clone();
-
-                map_builder.map.set(pos.x(), pos.y(), tile);
+                map_builder.map.set(pos.x(), pos.y(), wall.clone());
             });
             map_builder.rooms.push(room.clone());
         });
@@ -149,17 +153,15 @@ impl<T: Tile> MapBuilderAlgorithm<T> for RoomBuilder<T> {
             let corridor = self.connect_rooms(map_builder, room1, room2);
             let mut rng = rand::thread_rng();
             for cell in corridor.iter() {
-                let tile = map_builder.tiles.get("floor").unwrap().clone();
-                let wall_tile = map_builder.tiles.get("wall").unwrap().clone();
                 let offset = 3; //rng.gen_range(0..5);
                 for i in -offset + 1..offset {
-                    map_builder.map.set(cell.x() + i, cell.y(), tile.clone());
+                    map_builder.map.set(cell.x() + i, cell.y(), floor.clone());
                 }
 
                 // map_builder.map.set(cell.x(), cell.y(), tile);
             }
         }
 
-        map_builder
+        Ok(map_builder)
     }
 }
@@ -0,0 +1,420 @@
+use std::collections::HashSet;
+
+use crate::{IntVector2, Map, MapBuilder, MapBuilderAlgorithm, MapBuilderError, Tile, Vec2};
+
+fn is_open<T: Tile>(map: &Map<T>, pos: IntVector2) -> bool {
+    map.is_walkable(pos.x(), pos.y())
+}
+
+fn neighbors8(pos: IntVector2) -> [IntVector2; 8] {
+    let [x, y] = pos.as_array();
+    [
+        IntVector2::new(x - 1, y - 1),
+        IntVector2::new(x, y - 1),
+        IntVector2::new(x + 1, y - 1),
+        IntVector2::new(x - 1, y),
+        IntVector2::new(x + 1, y),
+        IntVector2::new(x - 1, y + 1),
+        IntVector2::new(x, y + 1),
+        IntVector2::new(x + 1, y + 1),
+    ]
+}
+
+fn open_neighbors<T: Tile>(map: &Map<T>, pos: IntVector2) -> usize {
+    neighbors8(pos).iter().filter(|n| is_open(map, **n)).count()
+}
+
+/// Applies a batch of changes computed on a snapshot of the map.
+fn apply<T: Tile>(map_builder: &mut MapBuilder<T>, changes: Vec<(IntVector2, T)>) {
+    for (pos, tile) in changes {
+        map_builder.map.set(pos.x(), pos.y(), tile);
+    }
+}
+
+/// Removes single-cell walls surrounded by floor and floor specks surrounded by walls.
+#[derive(Debug, Clone)]
+pub struct RemoveIsolatedTiles {
+    floor: String,
+    wall: String,
+}
+
+impl RemoveIsolatedTiles {
+    pub fn new() -> Self {
+        Self::with_tiles("floor", "wall")
+    }
+
+    /// Uses the given tile names instead of `"floor"` and `"wall"`.
+    pub fn with_tiles(floor: &str, wall: &str) -> Self {
+        Self {
+            floor: floor.to_string(),
+            wall: wall.to_string(),
+        }
+    }
+}
+
+impl Default for RemoveIsolatedTiles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Tile> MapBuilderAlgorithm<T> for RemoveIsolatedTiles {
+    fn build<'a>(
+        &self,
+        map_builder: &'a mut MapBuilder<T>,
+    ) -> Result<&'a mut MapBuilder<T>, MapBuilderError> {
+        let floor = map_builder.tile(&self.floor)?;
+        let wall = map_builder.tile(&self.wall)?;
+        let map = &map_builder.map;
+
+        let mut changes = Vec::new();
+        for pos in map.size().iter() {
+            if map.get(pos.x(), pos.y()).is_none() {
+                continue;
+            }
+
+            let open = open_neighbors(map, pos);
+            if is_open(map, pos) && open == 0 {
+                changes.push((pos, wall.clone()));
+            } else if !is_open(map, pos) && open == 8 {
+                changes.push((pos, floor.clone()));
+            }
+        }
+
+        apply(map_builder, changes);
+        Ok(map_builder)
+    }
+}
+
+/// Shrinks open areas: a floor cell with fewer than `threshold` open neighbours
+/// (out of 8) becomes a wall. Repeated `iterations` times.
+#[derive(Debug, Clone)]
+pub struct Erode {
+    iterations: usize,
+    threshold: usize,
+    wall: String,
+}
+
+impl Erode {
+    pub fn new(iterations: usize, threshold: usize) -> Self {
+        Self {
+            iterations,
+            threshold,
+            wall: "wall".to_string(),
+        }
+    }
+
+    /// Uses the given tile name instead of `"wall"`.
+    pub fn with_wall(mut self, wall: &str) -> Self {
+        self.wall = wall.to_string();
+        self
+    }
+}
+
+impl<T: Tile> MapBuilderAlgorithm<T> for Erode {
+    fn build<'a>(
+        &self,
+        map_builder: &'a mut MapBuilder<T>,
+    ) -> Result<&'a mut MapBuilder<T>, MapBuilderError> {
+        let wall = map_builder.tile(&self.wall)?;
+
+        for _ in 0..self.iterations {
+            let map = &map_builder.map;
+            let changes: Vec<_> = map
+                .size()
+                .iter()
+                .filter(|pos| is_open(map, *pos) && open_neighbors(map, *pos) < self.threshold)
+                .map(|pos| (pos, wall.clone()))
+                .collect();
+
+            if changes.is_empty() {
+                break;
+            }
+            apply(map_builder, changes);
+        }
+
+        Ok(map_builder)
+    }
+}
+
+/// Grows open areas: a non-walkable cell with at least `threshold` open
+/// neighbours (out of 8) becomes floor. Repeated `iterations` times.
+#[derive(Debug, Clone)]
+pub struct Dilate {
+    iterations: usize,
+    threshold: usize,
+    floor: String,
+}
+
+impl Dilate {
+    pub fn new(iterations: usize, threshold: usize) -> Self {
+        Self {
+            iterations,
+            threshold,
+            floor: "floor".to_string(),
+        }
+    }
+
+    /// Uses the given tile name instead of `"floor"`.
+    pub fn with_floor(mut self, floor: &str) -> Self {
+        self.floor = floor.to_string();
+        self
+    }
+}
+
+impl<T: Tile> MapBuilderAlgorithm<T> for Dilate {
+    fn build<'a>(
+        &self,
+        map_builder: &'a mut MapBuilder<T>,
+    ) -> Result<&'a mut MapBuilder<T>, MapBuilderError> {
+        let floor = map_builder.tile(&self.floor)?;
+
+        for _ in 0..self.iterations {
+            let map = &map_builder.map;
+            let changes: Vec<_> = map
+                .size()
+                .iter()
+                .filter(|pos| !is_open(map, *pos) && open_neighbors(map, *pos) >= self.threshold)
+                .map(|pos| (pos, floor.clone()))
+                .collect();
+
+            if changes.is_empty() {
+                break;
+            }
+            apply(map_builder, changes);
+        }
+
+        Ok(map_builder)
+    }
+}
+
+/// Turns small enclosed pockets of non-walkable cells into floor.
+///
+/// A pocket is a 4-connected group of non-walkable (or empty) cells with at
+/// most `max_size` cells that does not touch the map border.
+#[derive(Debug, Clone)]
+pub struct FillSmallHoles {
+    max_size: usize,
+    floor: String,
+}
+
+impl FillSmallHoles {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            floor: "floor".to_string(),
+        }
+    }
+
+    /// Uses the given tile name instead of `"floor"`.
+    pub fn with_floor(mut self, floor: &str) -> Self {
+        self.floor = floor.to_string();
+        self
+    }
+}
+
+impl<T: Tile> MapBuilderAlgorithm<T> for FillSmallHoles {
+    fn build<'a>(
+        &self,
+        map_builder: &'a mut MapBuilder<T>,
+    ) -> Result<&'a mut MapBuilder<T>, MapBuilderError> {
+        let floor = map_builder.tile(&self.floor)?;
+        let map = &map_builder.map;
+        let extent = map.size();
+
+        let mut seen = HashSet::<IntVector2>::new();
+        let mut changes = Vec::new();
+
+        for start in extent.iter() {
+            if is_open(map, start) || !seen.insert(start) {
+                continue;
+            }
+
+            let mut pocket = vec![start];
+            let mut stack = vec![start];
+            let mut touches_border = false;
+
+            while let Some(pos) = stack.pop() {
+                for n in map.neighbors(pos) {
+                    if !extent.contains(n.x(), n.y()) {
+                        touches_border = true;
+                        continue;
+                    }
+                    if !is_open(map, n) && seen.insert(n) {
+                        pocket.push(n);
+                        stack.push(n);
+                    }
+                }
+            }
+
+            if !touches_border && pocket.len() <= self.max_size {
+                changes.extend(pocket.into_iter().map(|pos| (pos, floor.clone())));
+            }
+        }
+
+        apply(map_builder, changes);
+        Ok(map_builder)
+    }
+}
+
+/// Surrounds open areas with walls `thickness` cells deep.
+///
+/// Only empty cells are filled, so existing non-walkable tiles (water, lava...)
+/// are left untouched.
+#[derive(Debug, Clone)]
+pub struct ThickenWalls {
+    thickness: usize,
+    wall: String,
+}
+
+impl ThickenWalls {
+    pub fn new(thickness: usize) -> Self {
+        Self {
+            thickness,
+            wall: "wall".to_string(),
+        }
+    }
+
+    /// Uses the given tile name instead of `"wall"`.
+    pub fn with_wall(mut self, wall: &str) -> Self {
+        self.wall = wall.to_string();
+        self
+    }
+}
+
+impl<T: Tile> MapBuilderAlgorithm<T> for ThickenWalls {
+    fn build<'a>(
+        &self,
+        map_builder: &'a mut MapBuilder<T>,
+    ) -> Result<&'a mut MapBuilder<T>, MapBuilderError> {
+        let wall = map_builder.tile(&self.wall)?;
+        let extent = map_builder.map.size();
+
+        let mut frontier: Vec<IntVector2> = extent
+            .iter()
+            .filter(|pos| is_open(&map_builder.map, *pos))
+            .collect();
+
+        for _ in 0..self.thickness {
+            let mut next = HashSet::new();
+            for pos in frontier.iter() {
+                for n in neighbors8(*pos) {
+                    if extent.contains(n.x(), n.y()) && map_builder.map.get(n.x(), n.y()).is_none()
+                    {
+                        next.insert(n);
+                    }
+                }
+            }
+
+            for pos in next.iter() {
+                map_builder.map.set(pos.x(), pos.y(), wall.clone());
+            }
+            frontier = next.into_iter().collect();
+        }
+
+        Ok(map_builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{test_support::WalkableTile, Dimension2D, IntExtent2D};
+
+    use super::*;
+
+    const FLOOR: WalkableTile = WalkableTile { walkable: true };
+    const WALL: WalkableTile = WalkableTile { walkable: false };
+
+    /// Builds a map from rows of `.` (floor), `#` (wall) and ` ` (empty).
+    fn builder_from(rows: &[&str]) -> MapBuilder<WalkableTile> {
+        let mut map_builder = MapBuilder::<WalkableTile>::new(
+            IntExtent2D::new(0, 0, rows[0].len(), rows.len()),
+            Dimension2D::new(24, 24),
+        );
+        map_builder.add_tile("floor", FLOOR);
+        map_builder.add_tile("wall", WALL);
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '.' => map_builder.map.set(x as i32, y as i32, FLOOR),
+                    '#' => map_builder.map.set(x as i32, y as i32, WALL),
+                    _ => {}
+                }
+            }
+        }
+        map_builder
+    }
+
+    fn walkable(map_builder: &MapBuilder<WalkableTile>, x: i32, y: i32) -> bool {
+        is_open(&map_builder.map, IntVector2::new(x, y))
+    }
+
+    #[test]
+    fn test_remove_isolated_tiles() {
+        let mut map_builder = builder_from(&[
+            "######", //
+            "#...##", //
+            "#.#.##", //
+            "#...##", //
+            "######", //
+            "######", //
+        ]);
+        map_builder.map.set(5, 5, FLOOR);
+        map_builder.add_step(&RemoveIsolatedTiles::new()).unwrap();
+
+        assert!(walkable(&map_builder, 2, 2));
+        assert!(!walkable(&map_builder, 5, 5));
+    }
+
+    #[test]
+    fn test_erode_and_dilate() {
+        let mut map_builder = builder_from(&[
+            "#######", //
+            "#.....#", //
+            "#.....#", //
+            "#.....#", //
+            "#######", //
+        ]);
+        map_builder.add_step(&Erode::new(1, 5)).unwrap();
+        assert!(!walkable(&map_builder, 1, 1));
+        assert!(walkable(&map_builder, 3, 2));
+
+        map_builder.add_step(&Dilate::new(1, 3)).unwrap();
+        assert!(walkable(&map_builder, 1, 1));
+    }
+
+    #[test]
+    fn test_fill_small_holes() {
+        let mut map_builder = builder_from(&[
+            "#######", //
+            "#.....#", //
+            "#.#...#", //
+            "#...###", //
+            "#######", //
+        ]);
+        map_builder.add_step(&FillSmallHoles::new(1)).unwrap();
+
+        assert!(walkable(&map_builder, 2, 2));
+        assert!(!walkable(&map_builder, 4, 3));
+        assert!(!walkable(&map_builder, 0, 0));
+    }
+
+    #[test]
+    fn test_thicken_walls() {
+        let mut map_builder = builder_from(&[
+            "       ", //
+            "       ", //
+            "   .   ", //
+            "       ", //
+            "       ", //
+        ]);
+        map_builder.add_step(&ThickenWalls::new(2)).unwrap();
+
+        assert!(walkable(&map_builder, 3, 2));
+        assert_eq!(map_builder.map.get(1, 0), Some(&WALL));
+        assert_eq!(map_builder.map.get(0, 0), None);
+        assert_eq!(map_builder.map.len(), 25);
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 
 use macroquad::{prelude::Color, texture::Texture2D};
+use serde::Deserialize;
 
 use crate::world::ItemKey;
 
@@ -9,15 +10,43 @@ pub enum TileSpriteInfo {
     SpriteSheet(&'static str),
     SingleSprite(Texture2D),
     Fill(Color),
+    /// A sprite picked by the auto-tile rules of the named group, see `AutoTileCache`.
+    AutoTile(&'static str),
+    /// A named animation of the `SpriteSheet`, played with the `Renderer` time.
+    Animation(&'static str),
     None,
 }
 
+/// A change of state a tile can go through, e.g. a wall being destroyed or a door broken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TileTransition {
+    /// The tile ran out of durability.
+    Destroyed,
+    Burnt,
+    Broken,
+    Opened,
+    Closed,
+}
+
 pub trait Tile:
     'static + Debug + Clone + Visible + Visited + FovOccluder + Walkable + ItemContainer
 {
     fn sprite_info(&self) -> TileSpriteInfo {
         TileSpriteInfo::None
     }
+
+    /// Remaining hit points of the tile, `None` if it cannot be damaged.
+    fn durability(&self) -> Option<u32> {
+        None
+    }
+
+    fn set_durability(&mut self, _durability: u32) {}
+
+    /// The tile this one turns into through `transition`, `None` if it does not change.
+    fn transition(&self, _transition: TileTransition) -> Option<Self> {
+        None
+    }
 }
 
 #[repr(transparent)]
@@ -50,6 +79,12 @@ pub trait FovOccluder {
     fn block_visibility(&self) -> VisibilityOcclusion {
         Self::VISIBLE
     }
+    /// Whether something over the tile, such as thick gas, stops sight. Set by the
+    /// `Environment` through `MapCommand::SetObscured`.
+    fn is_obscured(&self) -> bool {
+        false
+    }
+    fn set_obscured(&mut self, _obscured: bool) {}
 }
 
 pub trait Visible {
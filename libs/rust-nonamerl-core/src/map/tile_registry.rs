@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs,
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::{AutoTileMode, Tile, TileTransition, VisibilityOcclusion};
+
+/// Compact numeric identifier of a tile definition inside a `TileRegistry`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TileId(pub u16);
+
+/// A tile type as described in the data files.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TileDefinition {
+    pub name: String,
+    /// The name of the sprite in the `SpriteSheet`.
+    pub sprite: String,
+    #[serde(default)]
+    pub walkable: bool,
+    /// How much the tile blocks sight, from 0 (transparent) to 1 (opaque).
+    #[serde(default)]
+    pub opacity: f32,
+    #[serde(default = "default_movement_cost")]
+    pub movement_cost: u32,
+    #[serde(default)]
+    pub flags: Vec<String>,
+    /// Picks the sprite among the `<sprite>_<n>` variants from the neighbouring tiles.
+    #[serde(default)]
+    pub autotile: Option<AutoTileMode>,
+    /// Hit points of the tile, absent for indestructible tiles.
+    #[serde(default)]
+    pub durability: Option<u32>,
+    /// The name of the tile this one turns into through each transition.
+    #[serde(default)]
+    pub transitions: HashMap<TileTransition, String>,
+}
+
+fn default_movement_cost() -> u32 {
+    1
+}
+
+impl TileDefinition {
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+
+    /// The opacity expressed as the `VisibilityOcclusion` used by `FovOccluder`.
+    pub fn visibility(&self) -> VisibilityOcclusion {
+        VisibilityOcclusion::new((1. - self.opacity).clamp(0., 1.)).unwrap()
+    }
+}
+
+/// A tile type that can be created from a `TileDefinition`.
+pub trait FromTileDefinition: Tile {
+    fn from_definition(id: TileId, definition: &TileDefinition) -> Self;
+}
+
+#[derive(Debug)]
+pub enum TileRegistryError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    DuplicateTile(String),
+    InvalidOpacity(String, f32),
+    /// A transition of the first tile turns it into the second, which is not defined.
+    UnknownTransition(String, String),
+    TooManyTiles,
+}
+
+impl Display for TileRegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TileRegistryError::Io(e) => write!(f, "cannot read tile definitions: {}", e),
+            TileRegistryError::Parse(e) => write!(f, "invalid tile definitions: {}", e),
+            TileRegistryError::DuplicateTile(name) => write!(f, "tile `{}` defined twice", name),
+            TileRegistryError::InvalidOpacity(name, opacity) => {
+                write!(f, "tile `{}` has opacity {} outside 0..=1", name, opacity)
+            }
+            TileRegistryError::UnknownTransition(name, target) => {
+                write!(f, "tile `{}` turns into undefined tile `{}`", name, target)
+            }
+            TileRegistryError::TooManyTiles => write!(f, "too many tile definitions"),
+        }
+    }
+}
+
+impl std::error::Error for TileRegistryError {}
+
+impl From<std::io::Error> for TileRegistryError {
+    fn from(e: std::io::Error) -> Self {
+        TileRegistryError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for TileRegistryError {
+    fn from(e: serde_json::Error) -> Self {
+        TileRegistryError::Parse(e)
+    }
+}
+
+#[derive(Deserialize)]
+struct TileFile {
+    tiles: Vec<TileDefinition>,
+}
+
+/// Holds every tile definition, indexed both by name and by `TileId`.
+///
+/// Ids are assigned in registration order, so they are stable for a given data file.
+#[derive(Debug, Clone, Default)]
+pub struct TileRegistry {
+    definitions: Vec<TileDefinition>,
+    ids: HashMap<String, TileId>,
+}
+
+impl TileRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a JSON document of the form `{ "tiles": [ { "name": ..., "sprite": ... } ] }`.
+    pub fn from_json(json: &str) -> Result<Self, TileRegistryError> {
+        let file: TileFile = serde_json::from_str(json)?;
+        let mut registry = Self::new();
+        for definition in file.tiles {
+            registry.register(definition)?;
+        }
+        registry.validate()?;
+        Ok(registry)
+    }
+
+    /// Loads the tile definitions from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, TileRegistryError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn register(&mut self, definition: TileDefinition) -> Result<TileId, TileRegistryError> {
+        if self.ids.contains_key(&definition.name) {
+            return Err(TileRegistryError::DuplicateTile(definition.name));
+        }
+        if !(0.0..=1.0).contains(&definition.opacity) {
+            return Err(TileRegistryError::InvalidOpacity(
+                definition.name,
+                definition.opacity,
+            ));
+        }
+        let id = u16::try_from(self.definitions.len())
+            .map(TileId)
+            .map_err(|_| TileRegistryError::TooManyTiles)?;
+
+        self.ids.insert(definition.name.clone(), id);
+        self.definitions.push(definition);
+        Ok(id)
+    }
+
+    /// Checks that every transition turns its tile into a defined one. Call it once all the
+    /// definitions are registered, since a transition may name a tile registered after it.
+    pub fn validate(&self) -> Result<(), TileRegistryError> {
+        for definition in self.definitions.iter() {
+            if let Some(target) = definition
+                .transitions
+                .values()
+                .filter(|target| !self.ids.contains_key(*target))
+                .min()
+            {
+                return Err(TileRegistryError::UnknownTransition(
+                    definition.name.clone(),
+                    target.clone(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn id(&self, name: &str) -> Option<TileId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn get(&self, id: TileId) -> Option<&TileDefinition> {
+        self.definitions.get(id.0 as usize)
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&TileDefinition> {
+        self.id(name).and_then(|id| self.get(id))
+    }
+
+    /// The tile `id` turns into through `transition`, if the definition lists one.
+    pub fn transition(&self, id: TileId, transition: TileTransition) -> Option<TileId> {
+        self.get(id)
+            .and_then(|definition| definition.transitions.get(&transition))
+            .and_then(|name| self.id(name))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (TileId, &TileDefinition)> {
+        self.definitions
+            .iter()
+            .enumerate()
+            .map(|(i, definition)| (TileId(i as u16), definition))
+    }
+
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TILES: &str = r#"{
+        "tiles": [
+            { "name": "floor", "sprite": "floor", "walkable": true },
+            { "name": "wall", "sprite": "wall", "opacity": 1.0, "flags": ["diggable"],
+              "durability": 10, "transitions": { "destroyed": "floor" } },
+            { "name": "mud", "sprite": "mud", "walkable": true, "movement_cost": 3, "autotile": "blob" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_registry_from_json() {
+        let registry = TileRegistry::from_json(TILES).unwrap();
+
+        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.id("floor"), Some(TileId(0)));
+        assert_eq!(registry.id("lava"), None);
+
+        let wall = registry.get(TileId(1)).unwrap();
+        assert!(!wall.walkable);
+        assert!(wall.has_flag("diggable"));
+        assert_eq!(f32::from(wall.visibility()), 0.);
+        assert_eq!(registry.by_name("mud").unwrap().movement_cost, 3);
+        assert_eq!(registry.by_name("floor").unwrap().movement_cost, 1);
+        assert_eq!(
+            registry.by_name("mud").unwrap().autotile,
+            Some(AutoTileMode::Blob)
+        );
+        assert_eq!(registry.by_name("wall").unwrap().autotile, None);
+        assert_eq!(wall.durability, Some(10));
+        assert_eq!(
+            registry.transition(TileId(1), TileTransition::Destroyed),
+            Some(TileId(0))
+        );
+        assert_eq!(registry.transition(TileId(1), TileTransition::Burnt), None);
+    }
+
+    #[test]
+    fn test_registry_errors() {
+        let duplicated = r#"{ "tiles": [
+            { "name": "floor", "sprite": "floor" },
+            { "name": "floor", "sprite": "floor2" }
+        ] }"#;
+        assert!(matches!(
+            TileRegistry::from_json(duplicated),
+            Err(TileRegistryError::DuplicateTile(name)) if name == "floor"
+        ));
+
+        let opacity = r#"{ "tiles": [ { "name": "glass", "sprite": "glass", "opacity": 2.0 } ] }"#;
+        assert!(matches!(
+            TileRegistry::from_json(opacity),
+            Err(TileRegistryError::InvalidOpacity(_, _))
+        ));
+
+        let transition = r#"{ "tiles": [
+            { "name": "grass", "sprite": "grass", "transitions": { "burnt": "ash" } }
+        ] }"#;
+        assert!(matches!(
+            TileRegistry::from_json(transition),
+            Err(TileRegistryError::UnknownTransition(name, target))
+                if name == "grass" && target == "ash"
+        ));
+
+        assert!(matches!(
+            TileRegistry::from_json("{ \"tiles\": [ { \"name\": \"x\" } ] }"),
+            Err(TileRegistryError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,298 @@
+use std::{collections::HashMap, fmt::Debug, rc::Rc};
+
+use crate::{
+    spatial::SpatialIndex, status::StatusEffect, world::EntityKey, Action, ApplyStatusAction,
+    DamageAction, IntVector2, MapCommand, MapCommandAction, TeleportAction, Tile, Vec2,
+};
+
+/// When a trigger fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TriggerEvent {
+    /// An entity moved onto the cell.
+    Enter,
+    /// An entity moved off the cell.
+    Leave,
+    /// An entity is on the cell at the start of a turn, see `Triggers::standing_actions`.
+    Stand,
+}
+
+/// Builds the actions of a `TriggerEffect::Custom` from the entity and the trigger position.
+pub type TriggerFn<T> = Rc<dyn Fn(EntityKey, IntVector2) -> Vec<Box<dyn Action<T>>>>;
+
+/// What a trigger does to the entity that set it off.
+#[derive(Clone)]
+pub enum TriggerEffect<T: Tile> {
+    /// Hurts the entity regardless of its defense, e.g. a spike trap.
+    Damage(i32),
+    /// Moves the entity to another cell.
+    Teleport(IntVector2),
+    /// Puts the entity under a status effect, e.g. a poison needle.
+    Status(StatusEffect),
+    /// Changes the map, e.g. a pressure plate opening a door elsewhere.
+    Command(MapCommand<T>),
+    /// Any other effect, e.g. an alarm waking up the monsters around.
+    Custom(TriggerFn<T>),
+}
+
+impl<T: Tile> Debug for TriggerEffect<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriggerEffect::Damage(damage) => write!(f, "Damage({})", damage),
+            TriggerEffect::Teleport(to) => write!(f, "Teleport({:?})", to),
+            TriggerEffect::Status(effect) => write!(f, "Status({:?})", effect),
+            TriggerEffect::Command(command) => write!(f, "Command({:?})", command),
+            TriggerEffect::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl<T: Tile> TriggerEffect<T> {
+    fn actions(&self, entity: EntityKey, pos: IntVector2) -> Vec<Box<dyn Action<T>>> {
+        match self {
+            TriggerEffect::Damage(damage) => vec![Box::new(DamageAction::new(entity, *damage))],
+            TriggerEffect::Teleport(to) => vec![Box::new(TeleportAction::new(entity, *to))],
+            TriggerEffect::Status(effect) => {
+                vec![Box::new(ApplyStatusAction::new(entity, *effect))]
+            }
+            TriggerEffect::Command(command) => {
+                vec![Box::new(MapCommandAction::new(command.clone()))]
+            }
+            TriggerEffect::Custom(build) => build(entity, pos),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Trigger<T: Tile> {
+    pub on: TriggerEvent,
+    pub effect: TriggerEffect<T>,
+    /// Perception needed to spot the trigger, `None` once it is known.
+    pub hidden: Option<u32>,
+    /// Whether the trigger is removed after firing once.
+    pub once: bool,
+}
+
+impl<T: Tile> Trigger<T> {
+    pub fn new(on: TriggerEvent, effect: TriggerEffect<T>) -> Self {
+        Self {
+            on,
+            effect,
+            hidden: None,
+            once: false,
+        }
+    }
+
+    /// Hides the trigger until an entity with at least `difficulty` perception searches for it.
+    pub fn hidden(mut self, difficulty: u32) -> Self {
+        self.hidden = Some(difficulty);
+        self
+    }
+
+    pub fn once(mut self) -> Self {
+        self.once = true;
+        self
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden.is_some()
+    }
+}
+
+/// The triggers laid on the cells of a map.
+///
+/// Triggers do not act by themselves: `fire` and `standing_actions` turn them into
+/// actions to be performed through the `ActionQueue`. A trigger that fires is revealed.
+#[derive(Debug, Clone)]
+pub struct Triggers<T: Tile> {
+    cells: HashMap<IntVector2, Vec<Trigger<T>>>,
+}
+
+impl<T: Tile> Triggers<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, pos: IntVector2, trigger: Trigger<T>) {
+        self.cells.entry(pos).or_default().push(trigger);
+    }
+
+    pub fn at(&self, pos: IntVector2) -> &[Trigger<T>] {
+        self.cells.get(&pos).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The triggers on `pos` that have been detected, the ones a player should be shown.
+    pub fn known_at(&self, pos: IntVector2) -> impl Iterator<Item = &Trigger<T>> {
+        self.at(pos).iter().filter(|trigger| !trigger.is_hidden())
+    }
+
+    pub fn remove(&mut self, pos: IntVector2) -> Vec<Trigger<T>> {
+        self.cells.remove(&pos).unwrap_or_default()
+    }
+
+    pub fn reveal(&mut self, pos: IntVector2) {
+        if let Some(triggers) = self.cells.get_mut(&pos) {
+            triggers
+                .iter_mut()
+                .for_each(|trigger| trigger.hidden = None);
+        }
+    }
+
+    /// Reveals the hidden triggers within `radius` cells of `center` whose difficulty is at
+    /// most `perception`, returning the cells where something was found.
+    pub fn search(&mut self, center: IntVector2, radius: i32, perception: u32) -> Vec<IntVector2> {
+        let mut found = Vec::new();
+        for (pos, triggers) in self.cells.iter_mut() {
+            if (pos.x() - center.x()).abs() > radius || (pos.y() - center.y()).abs() > radius {
+                continue;
+            }
+            let mut revealed = false;
+            for trigger in triggers.iter_mut() {
+                if trigger
+                    .hidden
+                    .is_some_and(|difficulty| difficulty <= perception)
+                {
+                    trigger.hidden = None;
+                    revealed = true;
+                }
+            }
+            if revealed {
+                found.push(*pos);
+            }
+        }
+        found
+    }
+
+    /// The actions of the triggers on `pos` reacting to `event`, set off by `entity`.
+    pub fn fire(
+        &mut self,
+        pos: IntVector2,
+        event: TriggerEvent,
+        entity: EntityKey,
+    ) -> Vec<Box<dyn Action<T>>> {
+        let Some(triggers) = self.cells.get_mut(&pos) else {
+            return Vec::new();
+        };
+        let mut actions = Vec::new();
+        for trigger in triggers.iter_mut().filter(|trigger| trigger.on == event) {
+            trigger.hidden = None;
+            actions.extend(trigger.effect.actions(entity, pos));
+        }
+        triggers.retain(|trigger| !(trigger.once && trigger.on == event));
+        if triggers.is_empty() {
+            self.cells.remove(&pos);
+        }
+        actions
+    }
+
+    /// Fires the `Stand` triggers for every indexed entity on their cell. Call it once per turn.
+    pub fn standing_actions(&mut self, spatial: &SpatialIndex) -> Vec<Box<dyn Action<T>>> {
+        let mut cells = self
+            .cells
+            .iter()
+            .filter(|(_, triggers)| triggers.iter().any(|t| t.on == TriggerEvent::Stand))
+            .map(|(pos, _)| *pos)
+            .collect::<Vec<_>>();
+        cells.sort_by_key(|pos| (pos.y(), pos.x()));
+        cells
+            .into_iter()
+            .flat_map(|pos| {
+                spatial
+                    .at(pos)
+                    .iter()
+                    .flat_map(|entity| self.fire(pos, TriggerEvent::Stand, *entity))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+impl<T: Tile> Default for Triggers<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use slotmap::SlotMap;
+
+    use super::*;
+    use crate::test_support::TestTile;
+    use crate::TileTransition;
+
+    #[test]
+    fn test_search_and_fire() {
+        let entity = SlotMap::<EntityKey, ()>::with_key().insert(());
+        let trap = IntVector2::new(2, 2);
+        let mut triggers = Triggers::<TestTile>::new();
+        triggers.add(
+            trap,
+            Trigger::new(TriggerEvent::Enter, TriggerEffect::Damage(3)).hidden(5),
+        );
+        triggers.add(
+            trap,
+            Trigger::new(
+                TriggerEvent::Leave,
+                TriggerEffect::Command(MapCommand::TransformTile(
+                    IntVector2::new(0, 0),
+                    TileTransition::Opened,
+                )),
+            )
+            .once(),
+        );
+        assert_eq!(triggers.known_at(trap).count(), 1);
+
+        assert!(triggers.search(IntVector2::new(0, 0), 1, 10).is_empty());
+        assert!(triggers.search(IntVector2::new(1, 1), 1, 4).is_empty());
+        assert_eq!(triggers.search(IntVector2::new(1, 1), 1, 5), vec![trap]);
+        assert_eq!(triggers.known_at(trap).count(), 2);
+
+        assert_eq!(triggers.fire(trap, TriggerEvent::Enter, entity).len(), 1);
+        assert_eq!(triggers.fire(trap, TriggerEvent::Leave, entity).len(), 1);
+        // the pressure plate only works once
+        assert!(triggers.fire(trap, TriggerEvent::Leave, entity).is_empty());
+        assert_eq!(triggers.len(), 1);
+    }
+
+    #[test]
+    fn test_standing_actions() {
+        let entity = SlotMap::<EntityKey, ()>::with_key().insert(());
+        let fired = Rc::new(Cell::new(0));
+        let counter = fired.clone();
+        let mut triggers = Triggers::<TestTile>::new();
+        triggers.add(
+            IntVector2::new(1, 0),
+            Trigger::new(
+                TriggerEvent::Stand,
+                TriggerEffect::Custom(Rc::new(move |_, _| {
+                    counter.set(counter.get() + 1);
+                    Vec::new()
+                })),
+            )
+            .hidden(1),
+        );
+
+        let mut spatial = SpatialIndex::new();
+        spatial.insert(entity, IntVector2::new(0, 0));
+        triggers.standing_actions(&spatial);
+        assert_eq!(fired.get(), 0);
+
+        spatial.move_entity(entity, IntVector2::new(1, 0));
+        triggers.standing_actions(&spatial);
+        triggers.standing_actions(&spatial);
+        assert_eq!(fired.get(), 2);
+        assert!(!triggers.at(IntVector2::new(1, 0))[0].is_hidden());
+    }
+}
@@ -4,7 +4,9 @@ use macroquad::text::draw_text_ex;
 use macroquad::texture::{draw_texture_ex, DrawTextureParams};
 
 use crate::dimension::Dimension2;
-use crate::{Camera, Camera2D, Dimension2D, Map, SpriteSheet, Tile, TileSpriteInfo, Viewport};
+use crate::{
+    AutoTileCache, Camera, Camera2D, Dimension2D, Map, SpriteSheet, Tile, TileSpriteInfo, Viewport,
+};
 #[derive(Debug, Copy, Clone)]
 pub enum RenderOp<T: Tile> {
     DrawTile(i32, i32, T),
@@ -12,30 +14,109 @@ pub enum RenderOp<T: Tile> {
     DrawCircle,
     HighlightCell(i32, i32),
     FillCell(i32, i32, Color),
+    /// Draws the current frame of a named animation in the cell.
+    DrawAnimation(i32, i32, &'static str),
 }
 
 #[derive(Debug, Copy, Clone)]
 pub struct Renderer {
     pub cell_size: Dimension2D<usize>,
+    /// Seconds elapsed since the renderer was created, used to pick animation frames.
+    pub time: f32,
 }
 
 impl Renderer {
     pub fn from_map_cell_size(cell_size: Dimension2D<usize>) -> Self {
-        Self { cell_size }
+        Self {
+            cell_size,
+            time: 0.,
+        }
     }
 
     pub fn from_map<T: Tile>(map: &Map<T>) -> Self {
-        Self {
-            cell_size: map.cell_size(),
+        Self::from_map_cell_size(map.cell_size())
+    }
+
+    /// Advances the animation clock, call it once per frame with the frame time.
+    pub fn advance(&mut self, dt: f32) {
+        self.time += dt;
+    }
+
+    fn draw_animation(
+        &self,
+        camera: &Camera2D,
+        sprites: &SpriteSheet,
+        name: &str,
+        viewport_x: f32,
+        viewport_y: f32,
+    ) {
+        if let Some(sprite) = sprites
+            .get_animation(name)
+            .and_then(|animation| animation.frame_at(self.time))
+        {
+            self.draw_sprite(camera, sprites, sprite, viewport_x, viewport_y);
         }
     }
 
+    /// Renders the ops. Auto-tiled tiles are drawn with the sprite named after their group.
     pub fn batch_render<T: Tile>(
         &self,
         camera: &Camera2D,
         viewport: &Viewport,
         sprites: &SpriteSheet,
         render_ops: &Vec<RenderOp<T>>,
+    ) {
+        self.render(camera, viewport, sprites, render_ops, None);
+    }
+
+    /// Renders the ops, drawing auto-tiled tiles with the variants resolved in `autotiles`.
+    pub fn batch_render_autotiled<T: Tile>(
+        &self,
+        camera: &Camera2D,
+        viewport: &Viewport,
+        sprites: &SpriteSheet,
+        render_ops: &Vec<RenderOp<T>>,
+        autotiles: &AutoTileCache,
+    ) {
+        self.render(camera, viewport, sprites, render_ops, Some(autotiles));
+    }
+
+    fn draw_sprite(
+        &self,
+        camera: &Camera2D,
+        sprites: &SpriteSheet,
+        name: &str,
+        viewport_x: f32,
+        viewport_y: f32,
+    ) {
+        // unknown sprites are skipped rather than aborting the whole frame
+        let Ok((rect, texture)) = sprites.get_sprite(name) else {
+            return;
+        };
+
+        draw_texture_ex(
+            texture,
+            viewport_x,
+            viewport_y,
+            WHITE,
+            DrawTextureParams {
+                source: Some(*rect),
+                dest_size: Some(Vec2::new(
+                    self.cell_size.width() as f32 / camera.zoom_scale,
+                    self.cell_size.height() as f32 / camera.zoom_scale,
+                )),
+                ..Default::default()
+            },
+        );
+    }
+
+    fn render<T: Tile>(
+        &self,
+        camera: &Camera2D,
+        viewport: &Viewport,
+        sprites: &SpriteSheet,
+        render_ops: &Vec<RenderOp<T>>,
+        autotiles: Option<&AutoTileCache>,
     ) {
         for render_op in render_ops {
             match render_op {
@@ -75,22 +156,16 @@ impl Renderer {
                             );
                         }
                         TileSpriteInfo::SpriteSheet(name) => {
-                            let (rect, texture) = sprites.get_sprite(name);
-
-                            draw_texture_ex(
-                                texture,
-                                viewport_x,
-                                viewport_y,
-                                WHITE,
-                                DrawTextureParams {
-                                    source: Some(*rect),
-                                    dest_size: Some(Vec2::new(
-                                        self.cell_size.width() as f32 / camera.zoom_scale,
-                                        self.cell_size.height() as f32 / camera.zoom_scale,
-                                    )),
-                                    ..Default::default()
-                                },
-                            );
+                            self.draw_sprite(camera, sprites, name, viewport_x, viewport_y);
+                        }
+                        TileSpriteInfo::AutoTile(group) => {
+                            let name = autotiles
+                                .and_then(|cache| cache.sprite(*x, *y))
+                                .unwrap_or(group);
+                            self.draw_sprite(camera, sprites, name, viewport_x, viewport_y);
+                        }
+                        TileSpriteInfo::Animation(name) => {
+                            self.draw_animation(camera, sprites, name, viewport_x, viewport_y);
                         }
                         TileSpriteInfo::SingleSprite(texture) => {
                             draw_texture_ex(
@@ -177,6 +252,14 @@ impl Renderer {
                     //     },
                     // );
                 }
+                RenderOp::DrawAnimation(x, y, name) => {
+                    let (viewport_x, viewport_y) = camera.world_to_viewport(
+                        *x as f32 * self.cell_size.width() as f32,
+                        *y as f32 * self.cell_size.height() as f32,
+                        viewport,
+                    );
+                    self.draw_animation(camera, sprites, name, viewport_x, viewport_y);
+                }
                 RenderOp::DrawRectangle => {}
                 RenderOp::DrawCircle => {}
                 RenderOp::HighlightCell(x, y) => {
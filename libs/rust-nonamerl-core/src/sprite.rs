@@ -2,6 +2,9 @@
 use std::{collections::HashMap, hash::Hash};
 
 use macroquad::{prelude::Rect, texture::Texture2D};
+use serde::Deserialize;
+
+use crate::SpriteSheetError;
 
 #[derive(Debug, Clone)]
 pub struct AddSpriteOptions {
@@ -15,10 +18,104 @@ pub struct Sprite {
     pub spritesheet: u8,
 }
 
+/// What an animation does once its last frame has been shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopMode {
+    /// Stops on the last frame.
+    Once,
+    /// Starts again from the first frame.
+    #[default]
+    Loop,
+    /// Plays backwards to the first frame, then forwards again.
+    PingPong,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationFrame {
+    /// The name of the sprite shown in this frame.
+    pub sprite: String,
+    /// How long the frame is shown, in seconds.
+    pub duration: f32,
+}
+
+/// A named sequence of sprites of a `SpriteSheet`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    pub frames: Vec<AnimationFrame>,
+    pub loop_mode: LoopMode,
+}
+
+impl Animation {
+    pub fn new(frames: Vec<AnimationFrame>, loop_mode: LoopMode) -> Self {
+        Self { frames, loop_mode }
+    }
+
+    /// An animation showing every sprite for the same `frame_duration`.
+    pub fn uniform(sprites: &[&str], frame_duration: f32, loop_mode: LoopMode) -> Self {
+        Self::new(
+            sprites
+                .iter()
+                .map(|sprite| AnimationFrame {
+                    sprite: sprite.to_string(),
+                    duration: frame_duration,
+                })
+                .collect(),
+            loop_mode,
+        )
+    }
+
+    /// The length of one pass through the frames, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+
+    /// The index of the frame shown `time` seconds after the animation started.
+    pub fn frame_index_at(&self, time: f32) -> usize {
+        let count = self.frames.len();
+        let duration = self.duration();
+        if count <= 1 || duration <= 0. {
+            return 0;
+        }
+
+        // ping-pong plays the inner frames a second time, backwards, in each cycle:
+        // the k-th frame played is k, then 2 * (count - 1) - k on the way back
+        let (played, cycle) = match self.loop_mode {
+            LoopMode::PingPong => (
+                2 * count - 2,
+                2. * duration - self.frames[0].duration - self.frames[count - 1].duration,
+            ),
+            _ => (count, duration),
+        };
+        let frame = |k: usize| if k < count { k } else { 2 * (count - 1) - k };
+        let mut t = match self.loop_mode {
+            LoopMode::Once if time >= cycle => return count - 1,
+            LoopMode::Once => time.max(0.),
+            _ => time.rem_euclid(cycle),
+        };
+        for index in (0..played).map(frame) {
+            let frame_duration = self.frames[index].duration;
+            if t < frame_duration {
+                return index;
+            }
+            t -= frame_duration;
+        }
+        frame(played - 1)
+    }
+
+    /// The name of the sprite shown `time` seconds after the animation started.
+    pub fn frame_at(&self, time: f32) -> Option<&str> {
+        self.frames
+            .get(self.frame_index_at(time))
+            .map(|frame| frame.sprite.as_str())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SpriteSheet {
     pub textures: Vec<Texture2D>,
     pub sprites: HashMap<String, Sprite>,
+    pub animations: HashMap<String, Animation>,
 }
 
 impl SpriteSheet {
@@ -26,6 +123,7 @@ impl SpriteSheet {
         Self {
             textures: vec![texture],
             sprites: HashMap::new(),
+            animations: HashMap::new(),
         }
     }
 
@@ -57,8 +155,77 @@ impl SpriteSheet {
         );
     }
 
-    pub fn get_sprite(&self, name: &str) -> (&Rect, &Texture2D) {
-        let sprite = self.sprites.get(name).unwrap();
-        (&sprite.pos, &self.textures[sprite.spritesheet as usize])
+    pub fn add_animation(&mut self, name: &str, animation: Animation) {
+        self.animations.insert(name.to_owned(), animation);
+    }
+
+    pub fn get_animation(&self, name: &str) -> Option<&Animation> {
+        self.animations.get(name)
+    }
+
+    pub fn get_sprite(&self, name: &str) -> Result<(&Rect, &Texture2D), SpriteSheetError> {
+        let sprite = self
+            .sprites
+            .get(name)
+            .ok_or_else(|| SpriteSheetError::UnknownSprite(name.to_owned()))?;
+        let texture = self
+            .textures
+            .get(sprite.spritesheet as usize)
+            .ok_or_else(|| SpriteSheetError::UnknownTexture {
+                sprite: name.to_owned(),
+                texture: sprite.spritesheet as usize,
+            })?;
+        Ok((&sprite.pos, texture))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_animation_frames() {
+        let looped = Animation::uniform(&["a", "b", "c"], 0.5, LoopMode::Loop);
+        assert_eq!(looped.duration(), 1.5);
+        assert_eq!(looped.frame_at(0.), Some("a"));
+        assert_eq!(looped.frame_at(0.6), Some("b"));
+        assert_eq!(looped.frame_at(1.6), Some("a"));
+
+        let once = Animation::uniform(&["a", "b", "c"], 0.5, LoopMode::Once);
+        assert_eq!(once.frame_at(1.2), Some("c"));
+        assert_eq!(once.frame_at(10.), Some("c"));
+
+        let ping_pong = Animation::uniform(&["a", "b", "c"], 0.5, LoopMode::PingPong);
+        let frames = (0..6)
+            .map(|i| ping_pong.frame_at(i as f32 * 0.5 + 0.1).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(frames, vec!["a", "b", "c", "b", "a", "b"]);
+
+        let uneven = Animation::new(
+            vec![
+                AnimationFrame {
+                    sprite: "long".to_string(),
+                    duration: 1.,
+                },
+                AnimationFrame {
+                    sprite: "short".to_string(),
+                    duration: 0.1,
+                },
+            ],
+            LoopMode::Loop,
+        );
+        assert_eq!(uneven.frame_at(0.9), Some("long"));
+        assert_eq!(uneven.frame_at(1.05), Some("short"));
+
+        // the way back skips both ends: long, short, end, short, then long again
+        let mut frames = uneven.frames.clone();
+        frames.push(AnimationFrame {
+            sprite: "end".to_string(),
+            duration: 0.5,
+        });
+        let uneven = Animation::new(frames, LoopMode::PingPong);
+        assert_eq!(uneven.frame_index_at(1.3), 2);
+        assert_eq!(uneven.frame_index_at(1.65), 1);
+        assert_eq!(uneven.frame_index_at(1.75), 0);
     }
 }
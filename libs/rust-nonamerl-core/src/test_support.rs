@@ -0,0 +1,69 @@
+//! Fixtures shared by the unit tests.
+use crate::{world::ItemKey, FovOccluder, ItemContainer, Tile, Visible, Visited, Walkable};
+
+/// A tile that is either floor or wall.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WalkableTile {
+    pub(crate) walkable: bool,
+}
+impl Tile for WalkableTile {}
+impl Visible for WalkableTile {}
+impl Visited for WalkableTile {}
+impl FovOccluder for WalkableTile {}
+impl Walkable for WalkableTile {
+    fn is_walkable(&self) -> bool {
+        self.walkable
+    }
+}
+impl ItemContainer for WalkableTile {}
+
+/// A tile that only remembers whether it is visible.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct VisibleTile {
+    pub(crate) visible: bool,
+}
+impl Tile for VisibleTile {}
+impl Visible for VisibleTile {
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+    fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+}
+impl Visited for VisibleTile {}
+impl FovOccluder for VisibleTile {}
+impl Walkable for VisibleTile {}
+impl ItemContainer for VisibleTile {}
+
+/// A tile that relies on every trait default: walkable, transparent and never holding items.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct TestTile {}
+impl Tile for TestTile {}
+impl Visible for TestTile {}
+impl Visited for TestTile {}
+impl FovOccluder for TestTile {}
+impl Walkable for TestTile {}
+impl ItemContainer for TestTile {}
+
+/// A `TestTile` that keeps the items dropped on it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ItemTile {
+    pub(crate) items: Vec<ItemKey>,
+}
+impl Tile for ItemTile {}
+impl Visible for ItemTile {}
+impl Visited for ItemTile {}
+impl FovOccluder for ItemTile {}
+impl Walkable for ItemTile {}
+impl ItemContainer for ItemTile {
+    fn items(&self) -> &[ItemKey] {
+        &self.items
+    }
+    fn add_item(&mut self, item: ItemKey) {
+        self.items.push(item);
+    }
+    fn remove_item(&mut self, item: ItemKey) {
+        self.items.retain(|i| *i != item);
+    }
+}